@@ -1,71 +1,546 @@
-use alloc::vec::Vec;
-use alloy_primitives::{Bytes, U256};
-use alloy_rlp::Encodable as _;
-use alloy_rpc_types_eth::Account;
-use alloy_trie::Nibbles;
-use msgpacker::Unpackable as _;
-use valence_coprocessor::{DomainCircuit, StateProof};
+use alloc::{
+    format,
+    string::{String, ToString as _},
+    vec::Vec,
+};
+use alloy_consensus::{Header, ReceiptEnvelope, Transaction as _, TxEnvelope};
+use alloy_eips::eip2718::Decodable2718 as _;
+use alloy_primitives::{Bytes, B256, U256};
+use alloy_rlp::Decodable as _;
+use alloy_trie::{nodes::RlpNode, Nibbles, EMPTY_ROOT_HASH, KECCAK_EMPTY};
+use valence_coprocessor::{DomainCircuit, Hash, StateProof};
 
-use crate::{Ethereum, EthereumProvenAccount, EthereumStateProof, EthereumStorageProofArg};
+use crate::{
+    Ethereum, EthereumAccountProof, EthereumBlockProof, EthereumBlockRange, EthereumCodeProof,
+    EthereumCompactStateProof, EthereumError, EthereumHeaderProof, EthereumLog, EthereumNetwork,
+    EthereumProvenAccount, EthereumProvenAccounts, EthereumProvenBlock, EthereumProvenReceipt,
+    EthereumProvenTransaction, EthereumReceiptProof, EthereumStateProof, EthereumStorageProof,
+    EthereumStorageProofArg, EthereumTransactionProof,
+};
 
-impl DomainCircuit for Ethereum {
-    type Output = EthereumProvenAccount;
+use crate::verify::verify_trie_path;
+
+impl Ethereum {
+    /// Ensures the proof targets the domain of `network`.
+    ///
+    /// Proofs of every network share the same format, so circuits must check the domain before
+    /// trusting the proven values.
+    pub fn verify_network(proof: &StateProof, network: EthereumNetwork) -> anyhow::Result<()> {
+        if proof.domain != network.domain() {
+            return Err(EthereumError::DomainMismatch { network }.into());
+        }
+
+        Ok(())
+    }
+
+    /// Ensures the proof is from a block within `range`.
+    ///
+    /// The checked number is the validated block of the proof, which is the block of its
+    /// accounts. The receipts, transactions and headers are proven for an ancestor block instead,
+    /// checked by [Self::verify_receipts_in_range], [Self::verify_transactions_in_range] and
+    /// [Self::verify_block_in_range].
+    pub fn verify_block_range(
+        proof: &StateProof,
+        range: &EthereumBlockRange,
+    ) -> anyhow::Result<()> {
+        range.check(proof.number)?;
+
+        Ok(())
+    }
+
+    /// Verifies an account proof, as [DomainCircuit::verify], from a block within `range`.
+    pub fn verify_in_range(
+        proof: &StateProof,
+        range: &EthereumBlockRange,
+    ) -> anyhow::Result<EthereumProvenAccount> {
+        Self::verify_block_range(proof, range)?;
+
+        <Self as DomainCircuit>::verify(proof)
+    }
+
+    /// Verifies an account proof, as [DomainCircuit::verify], with the payload committed at the
+    /// `slot` of the account.
+    ///
+    /// See [EthereumProvenAccount::verify_payload].
+    pub fn verify_with_payload(
+        proof: &StateProof,
+        slot: U256,
+    ) -> anyhow::Result<EthereumProvenAccount> {
+        let account = <Self as DomainCircuit>::verify(proof)?;
+
+        account.verify_payload(slot)?;
+
+        Ok(account)
+    }
+
+    /// Verifies all the accounts of a state proof, as [Self::verify_accounts], from a block
+    /// within `range`.
+    pub fn verify_accounts_in_range(
+        proof: &StateProof,
+        range: &EthereumBlockRange,
+    ) -> anyhow::Result<EthereumProvenAccounts> {
+        Self::verify_block_range(proof, range)?;
+
+        Self::verify_accounts(proof)
+    }
+
+    /// Verifies all the accounts of a state proof against its state root.
+    ///
+    /// The proven accounts are returned in the order they were requested.
+    pub fn verify_accounts(proof: &StateProof) -> anyhow::Result<EthereumProvenAccounts> {
+        if EthereumCompactStateProof::is_compact(&proof.proof) {
+            return Self::verify_compact_accounts(proof);
+        }
 
-    fn verify(proof: &StateProof) -> anyhow::Result<Self::Output> {
         let root = proof.state_root;
-        let payload = proof.payload.clone();
-        let proof = EthereumStateProof::unpack(&proof.proof)?.1;
+        let payload = &proof.payload;
+        let proof = EthereumStateProof::try_from_slice(&proof.proof)
+            .map_err(|e| EthereumError::decode("state proof", e))?;
 
         let state_root = From::from(&root);
-        let key = alloy_primitives::keccak256(&proof.account);
-        let key = Nibbles::unpack(key);
-
-        let mut encoded_account = Vec::new();
-        Account {
-            nonce: proof.nonce,
-            balance: U256::from(proof.balance),
-            storage_root: proof.storage_root.into(),
-            code_hash: proof.code_hash.into(),
+
+        let mut accounts = proof
+            .accounts
+            .into_iter()
+            .map(|account| Self::verify_account(state_root, account, payload.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::verify_codes(&mut accounts, proof.codes)?;
+
+        Ok(EthereumProvenAccounts { accounts })
+    }
+
+    /// Verifies the bytecodes against the code hash of their proven accounts.
+    fn verify_codes(
+        accounts: &mut [EthereumProvenAccount],
+        codes: Vec<EthereumCodeProof>,
+    ) -> Result<(), EthereumError> {
+        for code in codes {
+            let account = accounts
+                .iter_mut()
+                .find(|a| a.account == code.account)
+                .ok_or_else(|| EthereumError::CodeProof {
+                    account: code.account.clone(),
+                    reason: "the account is not part of the proof".into(),
+                })?;
+
+            code.verify(&account.code_hash)?;
+
+            account.code.replace(code.code);
         }
-        .encode(&mut encoded_account);
 
-        let account_proof: Vec<_> = proof
-            .account_proof
+        Ok(())
+    }
+
+    fn verify_account(
+        state_root: B256,
+        proof: EthereumAccountProof,
+        payload: Vec<u8>,
+    ) -> Result<EthereumProvenAccount, EthereumError> {
+        proof.verify(state_root)?;
+
+        Ok(Self::proven_account(proof, payload))
+    }
+
+    /// Verifies a [EthereumCompactStateProof], hashing each distinct trie node once.
+    fn verify_compact_accounts(proof: &StateProof) -> anyhow::Result<EthereumProvenAccounts> {
+        let root = proof.state_root;
+        let payload = &proof.payload;
+        let proof = EthereumCompactStateProof::try_from_slice(&proof.proof)
+            .map_err(|e| EthereumError::decode("compact state proof", e))?;
+
+        let state_root = B256::from(root);
+        let nodes = CompactNodes::new(&proof.nodes);
+
+        let mut accounts = proof
+            .accounts
+            .into_iter()
+            .map(|account| {
+                let storage_proofs = account
+                    .storage_proofs
+                    .iter()
+                    .map(|p| EthereumStorageProof {
+                        key: p.key.clone(),
+                        value: p.value.clone(),
+                        proof: Vec::new(),
+                    })
+                    .collect();
+
+                let header = EthereumAccountProof {
+                    account: account.account,
+                    exists: account.exists,
+                    nonce: account.nonce,
+                    balance: account.balance,
+                    storage_root: account.storage_root,
+                    code_hash: account.code_hash,
+                    account_proof: Vec::new(),
+                    storage_proofs,
+                };
+
+                let key = alloy_primitives::keccak256(&header.account);
+                let key = Nibbles::unpack(key);
+                let encoded_account = header.encoded_account()?;
+
+                nodes
+                    .verify(
+                        state_root,
+                        &key,
+                        encoded_account.as_deref(),
+                        &account.account_proof,
+                    )
+                    .map_err(|e| header.account_error(e))?;
+
+                let root = header.storage_root.into();
+
+                for p in account.storage_proofs.iter() {
+                    let key = alloy_primitives::keccak256(&p.key);
+                    let key = Nibbles::unpack(key);
+
+                    nodes
+                        .verify(root, &key, p.value.as_deref(), &p.proof)
+                        .map_err(|e| header.storage_error(&p.key, e))?;
+                }
+
+                Ok(Self::proven_account(header, payload.clone()))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Self::verify_codes(&mut accounts, proof.codes)?;
+
+        Ok(EthereumProvenAccounts { accounts })
+    }
+
+    /// Verifies the receipts of an ancestor block against the validated state.
+    ///
+    /// The block must be one of the [Self::HISTORY_SERVE_WINDOW] blocks preceding the validated
+    /// block; the receipts of the validated block itself cannot be proven.
+    pub fn verify_receipts(proof: &StateProof) -> anyhow::Result<Vec<EthereumProvenReceipt>> {
+        Self::verify_receipts_in_range(proof, &EthereumBlockRange::default())
+    }
+
+    /// Verifies the receipts, as [Self::verify_receipts], of an ancestor block within `range`.
+    pub fn verify_receipts_in_range(
+        proof: &StateProof,
+        range: &EthereumBlockRange,
+    ) -> anyhow::Result<Vec<EthereumProvenReceipt>> {
+        let root = proof.state_root;
+        let number = proof.number;
+        let payload = &proof.payload;
+        let proof = EthereumReceiptProof::try_from_slice(&proof.proof)
+            .map_err(|e| EthereumError::decode("receipt proof", e))?;
+
+        let (header, block_hash) = Self::verify_header(root.into(), number, proof.block)?;
+
+        range.check(header.number)?;
+
+        proof
+            .receipts
+            .into_iter()
+            .map(|p| {
+                Self::verify_indexed(header.receipts_root, p.index, &p.receipt, &p.proof)?;
+
+                let receipt = ReceiptEnvelope::decode_2718(&mut p.receipt.as_slice())
+                    .map_err(|e| EthereumError::decode("receipt", e))?;
+
+                let logs = receipt
+                    .logs()
+                    .iter()
+                    .map(|log| EthereumLog {
+                        address: log.address.to_vec(),
+                        topics: log.topics().iter().map(|t| t.0).collect(),
+                        data: log.data.data.to_vec(),
+                    })
+                    .collect();
+
+                Ok(EthereumProvenReceipt {
+                    block_number: header.number,
+                    block_hash: block_hash.0,
+                    transaction_index: p.index,
+                    tx_type: receipt.tx_type() as u8,
+                    status: receipt.status(),
+                    logs,
+                    payload: payload.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Verifies the transactions of an ancestor block against the validated state.
+    ///
+    /// The block must be one of the [Self::HISTORY_SERVE_WINDOW] blocks preceding the validated
+    /// block; the transactions of the validated block itself cannot be proven.
+    pub fn verify_transactions(
+        proof: &StateProof,
+    ) -> anyhow::Result<Vec<EthereumProvenTransaction>> {
+        Self::verify_transactions_in_range(proof, &EthereumBlockRange::default())
+    }
+
+    /// Verifies the transactions, as [Self::verify_transactions], of an ancestor block within
+    /// `range`.
+    pub fn verify_transactions_in_range(
+        proof: &StateProof,
+        range: &EthereumBlockRange,
+    ) -> anyhow::Result<Vec<EthereumProvenTransaction>> {
+        let root = proof.state_root;
+        let number = proof.number;
+        let payload = &proof.payload;
+        let proof = EthereumTransactionProof::try_from_slice(&proof.proof)
+            .map_err(|e| EthereumError::decode("transaction proof", e))?;
+
+        let (header, block_hash) = Self::verify_header(root.into(), number, proof.block)?;
+
+        range.check(header.number)?;
+
+        proof
+            .transactions
+            .into_iter()
+            .map(|p| {
+                Self::verify_indexed(header.transactions_root, p.index, &p.transaction, &p.proof)?;
+
+                let tx = TxEnvelope::decode_2718(&mut p.transaction.as_slice())
+                    .map_err(|e| EthereumError::decode("transaction", e))?;
+
+                Ok(EthereumProvenTransaction {
+                    block_number: header.number,
+                    block_hash: block_hash.0,
+                    transaction_index: p.index,
+                    hash: alloy_primitives::keccak256(&p.transaction).0,
+                    tx_type: tx.tx_type() as u8,
+                    chain_id: tx.chain_id(),
+                    nonce: tx.nonce(),
+                    gas_limit: tx.gas_limit(),
+                    to: tx.to().map(|to| to.to_vec()),
+                    value: tx.value().to_be_bytes(),
+                    input: tx.input().to_vec(),
+                    signature_hash: tx.signature_hash().0,
+                    signature: tx.signature().as_bytes().to_vec(),
+                    transaction: p.transaction,
+                    payload: payload.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Verifies the header of an ancestor block against the validated state, with the accounts
+    /// proven against its state root.
+    ///
+    /// The anchored block must be one of the [Self::HISTORY_SERVE_WINDOW] blocks preceding the
+    /// validated block, and the proven block is reached by following the parent hashes of its
+    /// ancestors.
+    ///
+    /// The history contract only holds the hashes of the blocks before the validated block; its
+    /// own header is proven with [Self::verify_validated_header].
+    pub fn verify_block(proof: &StateProof) -> anyhow::Result<EthereumProvenBlock> {
+        Self::verify_block_in_range(proof, &EthereumBlockRange::default())
+    }
+
+    /// Verifies the header of an ancestor block, as [Self::verify_block], with a number within
+    /// `range`.
+    ///
+    /// The checked number is the one of the proven block, reached through its ancestors, rather
+    /// than the one of the anchored block.
+    pub fn verify_block_in_range(
+        proof: &StateProof,
+        range: &EthereumBlockRange,
+    ) -> anyhow::Result<EthereumProvenBlock> {
+        let root = proof.state_root;
+        let number = proof.number;
+        let payload = &proof.payload;
+        let proof = EthereumBlockProof::try_from_slice(&proof.proof)
+            .map_err(|e| EthereumError::decode("block proof", e))?;
+
+        let (mut header, mut hash) = Self::verify_header(root.into(), number, proof.block)?;
+
+        for ancestor in proof.ancestors {
+            let parent = Self::decode_header(&ancestor)?;
+            let parent_hash = alloy_primitives::keccak256(&ancestor);
+
+            if parent_hash != header.parent_hash
+                || parent.number.checked_add(1) != Some(header.number)
+            {
+                return Err(EthereumError::block_header(
+                    parent.number,
+                    format_args!("not the parent of block {}", header.number),
+                )
+                .into());
+            }
+
+            header = parent;
+            hash = parent_hash;
+        }
+
+        range.check(header.number)?;
+
+        let accounts = proof
+            .accounts
+            .into_iter()
+            .map(|account| Self::verify_account(header.state_root, account, payload.clone()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::proven_block(&header, hash, accounts, payload.clone()))
+    }
+
+    /// Verifies the RLP encoded header of the validated block of the proof against its
+    /// `block_hash`.
+    ///
+    /// The light client wrapper circuit commits the execution block hash of the validated block
+    /// with its state root, so `block_hash` must be taken from that commitment, and never from
+    /// the witness. The header must then match the state root and number of the proof.
+    pub fn verify_validated_header(
+        proof: &StateProof,
+        header: &[u8],
+        block_hash: &Hash,
+    ) -> anyhow::Result<EthereumProvenBlock> {
+        let decoded = Self::decode_header(header)?;
+        let hash = alloy_primitives::keccak256(header);
+
+        if hash != B256::from(*block_hash) {
+            return Err(EthereumError::block_header(
+                decoded.number,
+                "the header doesn't match the validated block hash",
+            )
+            .into());
+        }
+
+        if decoded.number != proof.number || decoded.state_root != B256::from(proof.state_root) {
+            return Err(EthereumError::block_header(
+                decoded.number,
+                "not the header of the validated block",
+            )
+            .into());
+        }
+
+        Ok(Self::proven_block(
+            &decoded,
+            hash,
+            Vec::new(),
+            proof.payload.clone(),
+        ))
+    }
+
+    fn proven_block(
+        header: &Header,
+        hash: B256,
+        accounts: Vec<EthereumProvenAccount>,
+        payload: Vec<u8>,
+    ) -> EthereumProvenBlock {
+        EthereumProvenBlock {
+            number: header.number,
+            hash: hash.0,
+            parent_hash: header.parent_hash.0,
+            state_root: header.state_root.0,
+            receipts_root: header.receipts_root.0,
+            transactions_root: header.transactions_root.0,
+            timestamp: header.timestamp,
+            base_fee_per_gas: header.base_fee_per_gas,
+            accounts,
+            payload,
+        }
+    }
+
+    /// Verifies the inclusion of a value in a trie keyed by RLP encoded indexes, as the receipts
+    /// and transactions tries of a block.
+    fn verify_indexed(
+        root: B256,
+        index: u64,
+        value: &[u8],
+        proof: &[Vec<u8>],
+    ) -> Result<(), EthereumError> {
+        let key = Nibbles::unpack(alloy_rlp::encode(index));
+        let proof: Vec<_> = proof
             .iter()
             .map(|p| Bytes::copy_from_slice(p.as_slice()))
             .collect();
 
-        alloy_trie::proof::verify_proof(
-            state_root,
-            key,
-            Some(encoded_account),
-            account_proof.iter(),
-        )
-        .map_err(|e| anyhow::anyhow!("account proof failed: {e}"))?;
-
-        let root = proof.storage_root.into();
-
-        for p in proof.storage_proofs.iter() {
-            let key = alloy_primitives::keccak256(&p.key);
-            let key = Nibbles::unpack(key);
-            let value = p.value.as_ref().cloned();
-
-            let proof: Vec<_> = p
-                .proof
-                .iter()
-                .map(|p| Bytes::copy_from_slice(p.as_slice()))
-                .collect();
-
-            alloy_trie::proof::verify_proof(root, key, value, &proof)
-                .map_err(|e| anyhow::anyhow!("storage proof failed: {e}"))?;
+        alloy_trie::proof::verify_proof(root, key, Some(value.to_vec()), &proof).map_err(|e| {
+            EthereumError::InclusionProof {
+                index,
+                reason: e.to_string(),
+            }
+        })
+    }
+
+    /// Verifies an ancestor block header against the EIP-2935 history of the validated state.
+    ///
+    /// Fails for the validated block and the later ones, as their hashes are not in its history.
+    ///
+    /// Returns the decoded header with its hash.
+    fn verify_header(
+        state_root: B256,
+        number: u64,
+        proof: EthereumHeaderProof,
+    ) -> Result<(Header, B256), EthereumError> {
+        let header = Self::decode_header(&proof.header)?;
+        let hash = alloy_primitives::keccak256(&proof.header);
+        let error = |reason: &str| EthereumError::block_header(header.number, reason);
+
+        if header.number >= number || number - header.number > Self::HISTORY_SERVE_WINDOW {
+            return Err(EthereumError::block_header(
+                header.number,
+                format_args!("outside of the history window of block {number}"),
+            ));
+        }
+
+        if proof.history.account != Self::HISTORY_STORAGE_ADDRESS {
+            return Err(error("the history proof is not for the history contract"));
+        }
+
+        let slot = U256::from(header.number % Self::HISTORY_SERVE_WINDOW);
+        let value = alloy_rlp::encode(U256::from_be_bytes(hash.0));
+
+        let [storage] = proof.history.storage_proofs.as_slice() else {
+            return Err(error("expected a single history slot"));
+        };
+
+        if storage.key != slot.to_be_bytes::<32>() {
+            return Err(error("the history slot doesn't match the block"));
+        }
+
+        if storage.value.as_deref() != Some(value.as_slice()) {
+            return Err(error("the block hash doesn't match the history"));
+        }
+
+        Self::verify_account(state_root, proof.history, Vec::new())?;
+
+        Ok((header, hash))
+    }
+
+    fn decode_header(header: &[u8]) -> Result<Header, EthereumError> {
+        let mut bytes = header;
+        let header =
+            Header::decode(&mut bytes).map_err(|e| EthereumError::decode("block header", e))?;
+
+        if !bytes.is_empty() {
+            return Err(EthereumError::decode(
+                "block header",
+                "trailing bytes after the header",
+            ));
         }
 
-        let EthereumStateProof {
+        Ok(header)
+    }
+
+    /// Computes the trie leaf of the account, or `None` if it is proven absent.
+    fn proven_account(proof: EthereumAccountProof, payload: Vec<u8>) -> EthereumProvenAccount {
+        let EthereumAccountProof {
             account,
+            exists,
+            mut nonce,
+            mut balance,
+            mut storage_root,
+            mut code_hash,
             storage_proofs,
             ..
         } = proof;
 
+        // a non-existent account is reported as an empty account
+        if !exists {
+            nonce = 0;
+            balance = [0; 32];
+            storage_root = EMPTY_ROOT_HASH.0;
+            code_hash = KECCAK_EMPTY.0;
+        }
+
         let storage = storage_proofs
             .into_iter()
             .map(|p| EthereumStorageProofArg {
@@ -74,10 +549,73 @@ impl DomainCircuit for Ethereum {
             })
             .collect();
 
-        Ok(EthereumProvenAccount {
+        EthereumProvenAccount {
             account,
+            exists,
+            nonce,
+            balance,
+            storage_root,
+            code_hash,
+            code: None,
             storage,
             payload,
-        })
+        }
+    }
+}
+
+impl DomainCircuit for Ethereum {
+    type Output = EthereumProvenAccount;
+
+    /// Verifies all the accounts of the proof, returning the first requested one.
+    ///
+    /// The other accounts of a multi-account proof are available with
+    /// [Ethereum::verify_accounts].
+    fn verify(proof: &StateProof) -> anyhow::Result<Self::Output> {
+        Self::verify_accounts(proof)?
+            .accounts
+            .into_iter()
+            .next()
+            .ok_or_else(|| EthereumError::config("the state proof has no account").into())
+    }
+}
+
+/// Deduplicated trie nodes with their references computed once.
+struct CompactNodes<'a> {
+    nodes: &'a [Vec<u8>],
+    references: Vec<RlpNode>,
+}
+
+impl<'a> CompactNodes<'a> {
+    fn new(nodes: &'a [Vec<u8>]) -> Self {
+        let references = nodes.iter().map(|n| RlpNode::from_rlp(n)).collect();
+
+        Self { nodes, references }
+    }
+
+    /// Verifies the path of a key, given as indexes of the deduplicated nodes.
+    ///
+    /// Fails with the reason of the mismatch, reported as the account or storage proof error of
+    /// the caller.
+    fn verify(
+        &self,
+        root: B256,
+        key: &Nibbles,
+        expected: Option<&[u8]>,
+        path: &[u32],
+    ) -> Result<(), String> {
+        let path = path
+            .iter()
+            .map(|i| {
+                let i = *i as usize;
+
+                self.nodes
+                    .get(i)
+                    .zip(self.references.get(i))
+                    .map(|(node, reference)| (node.as_slice(), reference.clone()))
+                    .ok_or_else(|| format!("node index {i} out of bounds"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        verify_trie_path(root, key, expected, path)
     }
 }
@@ -0,0 +1,156 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use msgpacker::{MsgPacker, Packable as _, Unpackable as _};
+use serde::{Deserialize, Serialize};
+use valence_coprocessor::Hash;
+
+use crate::{EthereumAccountProof, EthereumCodeProof, EthereumStateProof};
+
+/// A [EthereumStateProof] with deduplicated trie nodes.
+///
+/// Every distinct node of the account and storage proofs is stored once in `nodes`, and the
+/// Merkle paths reference them by index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumCompactStateProof {
+    pub state_root: Hash,
+    /// Distinct trie nodes referenced by the proofs.
+    pub nodes: Vec<Vec<u8>>,
+    /// Accounts proven against the state root.
+    pub accounts: Vec<EthereumCompactAccountProof>,
+    /// Bytecode of the accounts that requested it.
+    pub codes: Vec<EthereumCodeProof>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumCompactAccountProof {
+    pub account: Vec<u8>,
+    /// `false` if the proof attests the account is absent from the state trie.
+    pub exists: bool,
+    pub nonce: u64,
+    /// Big-endian encoded account balance.
+    pub balance: [u8; 32],
+    pub storage_root: Hash,
+    pub code_hash: Hash,
+    /// Indexes of the account proof nodes.
+    pub account_proof: Vec<u32>,
+    pub storage_proofs: Vec<EthereumCompactStorageProof>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumCompactStorageProof {
+    /// The computed storage key for the storage slot.
+    pub key: Vec<u8>,
+
+    /// The RLP encoded slot value.
+    pub value: Option<Vec<u8>>,
+
+    /// Indexes of the Merkle storage proof nodes.
+    pub proof: Vec<u32>,
+}
+
+impl EthereumCompactStateProof {
+    /// Wire format version, following [EthereumStateProof::MAGIC].
+    ///
+    /// Compact proofs count up from `0x81`, so [Self::is_compact] tells them apart from the
+    /// state proofs with the version byte alone.
+    pub const VERSION: u8 = 0x81;
+
+    /// Returns `true` if the bytes are a compact proof.
+    pub fn is_compact(bytes: &[u8]) -> bool {
+        matches!(bytes, [EthereumStateProof::MAGIC, Self::VERSION, ..])
+    }
+
+    /// Serializes the proof into its versioned wire format.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(512);
+
+        bytes.push(EthereumStateProof::MAGIC);
+        bytes.push(Self::VERSION);
+
+        self.pack(&mut bytes);
+
+        bytes
+    }
+
+    /// Deserializes a compact proof.
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, msgpacker::Error> {
+        match bytes {
+            [EthereumStateProof::MAGIC, Self::VERSION, proof @ ..] => Ok(Self::unpack(proof)?.1),
+            _ => Err(msgpacker::Error::UnexpectedFormatTag),
+        }
+    }
+}
+
+#[derive(Default)]
+struct NodeSet {
+    nodes: Vec<Vec<u8>>,
+    index: BTreeMap<Vec<u8>, u32>,
+}
+
+impl NodeSet {
+    fn insert_path(&mut self, path: Vec<Vec<u8>>) -> Vec<u32> {
+        path.into_iter()
+            .map(|node| match self.index.get(&node) {
+                Some(i) => *i,
+                None => {
+                    let i = self.nodes.len() as u32;
+
+                    self.index.insert(node.clone(), i);
+                    self.nodes.push(node);
+
+                    i
+                }
+            })
+            .collect()
+    }
+}
+
+impl From<EthereumStateProof> for EthereumCompactStateProof {
+    fn from(proof: EthereumStateProof) -> Self {
+        let mut nodes = NodeSet::default();
+
+        let accounts = proof
+            .accounts
+            .into_iter()
+            .map(|account| {
+                let EthereumAccountProof {
+                    account,
+                    exists,
+                    nonce,
+                    balance,
+                    storage_root,
+                    code_hash,
+                    account_proof,
+                    storage_proofs,
+                } = account;
+
+                let account_proof = nodes.insert_path(account_proof);
+                let storage_proofs = storage_proofs
+                    .into_iter()
+                    .map(|p| EthereumCompactStorageProof {
+                        key: p.key,
+                        value: p.value,
+                        proof: nodes.insert_path(p.proof),
+                    })
+                    .collect();
+
+                EthereumCompactAccountProof {
+                    account,
+                    exists,
+                    nonce,
+                    balance,
+                    storage_root,
+                    code_hash,
+                    account_proof,
+                    storage_proofs,
+                }
+            })
+            .collect();
+
+        Self {
+            state_root: proof.state_root,
+            nodes: nodes.nodes,
+            accounts,
+            codes: proof.codes,
+        }
+    }
+}
@@ -1,231 +1,1401 @@
-use alloc::{string::String, vec::Vec};
-use alloy_primitives::U256;
-use alloy_rpc_types_eth::EIP1186AccountProofResponse;
+use alloc::{
+    format,
+    string::{String, ToString as _},
+    vec,
+    vec::Vec,
+};
+use alloy_consensus::{Receipt, ReceiptEnvelope, ReceiptWithBloom};
+use alloy_eips::eip2718::Encodable2718 as _;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_rpc_types_eth::{
+    EIP1186AccountProofResponse, Header, Log, Transaction, TransactionReceipt,
+};
 use alloy_serde::JsonStorageKey;
-use msgpacker::Packable as _;
+use alloy_trie::{proof::ProofRetainer, HashBuilder, Nibbles, KECCAK_EMPTY};
+use core::iter;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use valence_coprocessor::{DomainController, DomainData, Hash, StateProof, ValidatedDomainBlock};
+use valence_coprocessor::{DomainController, Hash, StateProof, ValidatedDomainBlock};
 use valence_coprocessor_wasm::abi;
 
 use crate::{
-    Ethereum, EthereumStateProof, EthereumStateProofArgs, EthereumStorageProof,
-    EthereumStorageProofArg,
+    Ethereum, EthereumAccountProof, EthereumAccountProofArgs, EthereumArrayLayout,
+    EthereumBlockProof, EthereumBlockProofArgs, EthereumCodeProof, EthereumCompactStateProof,
+    EthereumError, EthereumHeaderProof, EthereumMappingKey, EthereumNetwork, EthereumPackedLayout,
+    EthereumProofArgs, EthereumProofMode, EthereumProxySlot, EthereumReceiptInclusionProof,
+    EthereumReceiptProof, EthereumReceiptProofArgs, EthereumSlotPosition, EthereumStateProof,
+    EthereumStateProofArgs, EthereumStorageLayout, EthereumStorageProof, EthereumStorageProofArg,
+    EthereumStorageValues, EthereumTransactionInclusionProof, EthereumTransactionProof,
+    EthereumTransactionProofArgs,
 };
 
 impl Ethereum {
     pub fn get_latest_block() -> anyhow::Result<ValidatedDomainBlock> {
-        abi::get_latest_block(Self::ID)?.ok_or_else(|| anyhow::anyhow!("no valid domain block"))
+        Self::get_network_latest_block(EthereumNetwork::Mainnet)
+    }
+
+    /// Returns the latest validated block of the `network` domain.
+    pub fn get_network_latest_block(
+        network: EthereumNetwork,
+    ) -> anyhow::Result<ValidatedDomainBlock> {
+        abi::get_latest_block(network.id())?.ok_or_else(|| {
+            EthereumError::Rpc {
+                method: "get_latest_block".into(),
+                reason: format!("no validated block of the {network} domain"),
+            }
+            .into()
+        })
     }
 
+    /// Requests the state proof of `args` to the domain of their network.
     pub fn get_state_proof(args: &Value) -> anyhow::Result<StateProof> {
-        abi::get_state_proof(Self::ID, args)
+        let network: EthereumNetwork = match args.get("network") {
+            Some(network) => {
+                serde_json::from_value(network.clone()).map_err(EthereumError::config)?
+            }
+            None => EthereumNetwork::Mainnet,
+        };
+
+        abi::get_state_proof(network.id(), args)
     }
 
     pub fn encode_proof(proof: Value, args: EthereumStateProofArgs) -> anyhow::Result<StateProof> {
-        let proof: EIP1186AccountProofResponse = serde_json::from_value(proof)?;
+        Self::encode_proofs(vec![proof], args)
+    }
+
+    /// Encodes the `eth_getProof` responses of all the requested accounts into a single proof.
+    ///
+    /// The responses must follow the order of [EthereumStateProofArgs::to_accounts].
+    pub fn encode_proofs(
+        proofs: Vec<Value>,
+        args: EthereumStateProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        Self::encode_proofs_with_codes(proofs, Vec::new(), args)
+    }
+
+    /// Encodes the `eth_getProof` responses of all the requested accounts, with the `eth_getCode`
+    /// responses of the accounts requesting their bytecode, into a single proof.
+    ///
+    /// Both follow the order of [EthereumStateProofArgs::to_accounts].
+    pub fn encode_proofs_with_codes(
+        proofs: Vec<Value>,
+        codes: Vec<Value>,
+        args: EthereumStateProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        let accounts = args.to_accounts();
+        let with_code: Vec<_> = accounts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, a)| a.code.then_some(i))
+            .collect();
+
+        if codes.len() != with_code.len() {
+            return Err(EthereumError::config(format_args!(
+                "expected {} bytecodes, got {}",
+                with_code.len(),
+                codes.len()
+            ))
+            .into());
+        }
+
+        if proofs.len() != accounts.len() {
+            return Err(EthereumError::config(format_args!(
+                "expected {} account proofs, got {}",
+                accounts.len(),
+                proofs.len()
+            ))
+            .into());
+        }
+
+        let state_root = B256::from(args.root);
+        let accounts = proofs
+            .into_iter()
+            .zip(accounts)
+            .enumerate()
+            .map(|(i, (proof, account))| {
+                // the fetched slots are the ones of the primary account
+                let fetched = match i {
+                    0 => args.fetched.as_slice(),
+                    _ => &[],
+                };
+
+                let proof = Self::encode_account_proof(proof, account, args.values, fetched)?;
+
+                proof.verify(state_root)?;
+
+                Ok(proof)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let codes = with_code
+            .into_iter()
+            .zip(codes)
+            .map(|(i, code)| Self::encode_code_proof(&accounts[i], code))
+            .collect::<anyhow::Result<_>>()?;
+
+        let EthereumStateProofArgs {
+            payload,
+            root,
+            block,
+            compact,
+            network,
+            ..
+        } = args;
+
+        let proof = EthereumStateProof {
+            state_root: root,
+            accounts,
+            codes,
+        };
+
+        let proof = if compact {
+            EthereumCompactStateProof::from(proof).to_vec()
+        } else {
+            proof.to_vec()
+        };
+
+        let domain = network.domain();
+        let state_root = root;
+
+        Ok(StateProof {
+            domain,
+            state_root,
+            payload,
+            proof,
+            number: block,
+        })
+    }
+
+    /// Encodes the receipts of the target block into a proof anchored at the validated block.
+    ///
+    /// Takes the `eth_getBlockByNumber` and `eth_getBlockReceipts` responses of the target block,
+    /// and the `eth_getProof` response of its history slot at the validated block.
+    pub fn encode_receipt_proof(
+        block: Value,
+        receipts: Value,
+        history: Value,
+        args: EthereumReceiptProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        let (header, block) = Self::encode_header_proof(block, history)?;
+
+        if header.number != args.target {
+            return Err(EthereumError::block_header(
+                header.number,
+                format_args!("expected block {}", args.target),
+            )
+            .into());
+        }
+
+        let receipts: Vec<TransactionReceipt> = serde_json::from_value(receipts)
+            .map_err(|e| EthereumError::decode("eth_getBlockReceipts response", e))?;
+        let receipts = receipts
+            .into_iter()
+            .map(|r| Self::primitive_receipt(r.inner).map(|r| r.encoded_2718()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let (receipts_root, proofs) = Self::ordered_trie_proofs(&receipts, &args.transactions)?;
+
+        if receipts_root != header.inner.receipts_root {
+            return Err(EthereumError::block_header(
+                header.number,
+                "the receipts don't match the receipts root",
+            )
+            .into());
+        }
+
+        let receipts = args
+            .transactions
+            .iter()
+            .zip(proofs)
+            .map(|(index, proof)| EthereumReceiptInclusionProof {
+                index: *index,
+                receipt: receipts[*index as usize].clone(),
+                proof,
+            })
+            .collect();
+
+        let EthereumReceiptProofArgs {
+            block: number,
+            root,
+            payload,
+            network,
+            ..
+        } = args;
+
+        let proof = EthereumReceiptProof {
+            state_root: root,
+            block,
+            receipts,
+        };
+
+        let domain = network.domain();
+
+        Ok(StateProof {
+            domain,
+            state_root: root,
+            payload,
+            proof: proof.to_vec(),
+            number,
+        })
+    }
+
+    /// Encodes the transactions of the target block into a proof anchored at the validated block.
+    ///
+    /// Takes the `eth_getBlockByNumber` response of the target block, with its full
+    /// transactions, and the `eth_getProof` response of its history slot at the validated block.
+    pub fn encode_transaction_proof(
+        block: Value,
+        history: Value,
+        args: EthereumTransactionProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        let transactions: Vec<Transaction> = serde_json::from_value(block["transactions"].clone())
+            .map_err(|e| EthereumError::decode("eth_getBlockByNumber transactions", e))?;
+        let (header, block) = Self::encode_header_proof(block, history)?;
+
+        if header.number != args.target {
+            return Err(EthereumError::block_header(
+                header.number,
+                format_args!("expected block {}", args.target),
+            )
+            .into());
+        }
+
+        let transactions: Vec<_> = transactions
+            .into_iter()
+            .map(|tx| tx.inner.encoded_2718())
+            .collect();
+
+        let hashes: Vec<_> = transactions
+            .iter()
+            .map(|tx| alloy_primitives::keccak256(tx).0)
+            .collect();
+
+        let indexes = args
+            .hashes
+            .iter()
+            .map(|hash| {
+                hashes
+                    .iter()
+                    .position(|h| h == hash)
+                    .map(|i| i as u64)
+                    .ok_or_else(|| {
+                        EthereumError::block_header(
+                            header.number,
+                            format_args!("transaction {} is not in the block", B256::from(*hash)),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let indexes: Vec<_> = args.transactions.iter().copied().chain(indexes).collect();
+        let (transactions_root, proofs) = Self::ordered_trie_proofs(&transactions, &indexes)?;
+
+        if transactions_root != header.inner.transactions_root {
+            return Err(EthereumError::block_header(
+                header.number,
+                "the transactions don't match the transactions root",
+            )
+            .into());
+        }
+
+        let transactions = indexes
+            .iter()
+            .zip(proofs)
+            .map(|(index, proof)| EthereumTransactionInclusionProof {
+                index: *index,
+                transaction: transactions[*index as usize].clone(),
+                proof,
+            })
+            .collect();
+
+        let EthereumTransactionProofArgs {
+            block: number,
+            root,
+            payload,
+            network,
+            ..
+        } = args;
+
+        let proof = EthereumTransactionProof {
+            state_root: root,
+            block,
+            transactions,
+        };
+
+        let domain = network.domain();
+
+        Ok(StateProof {
+            domain,
+            state_root: root,
+            payload,
+            proof: proof.to_vec(),
+            number,
+        })
+    }
+
+    /// Encodes the header of the target block into a proof anchored at the validated block.
+    ///
+    /// Takes the `eth_getBlockByNumber` response of the target block, the `eth_getProof` response
+    /// of its history slot at the validated block, and the `eth_getProof` responses of the
+    /// requested accounts at the target block.
+    pub fn encode_block_proof(
+        block: Value,
+        history: Value,
+        proofs: Vec<Value>,
+        args: EthereumBlockProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        Self::encode_historical_block_proof(block, Vec::new(), history, proofs, args)
+    }
+
+    /// Encodes the header of a target block older than the history window.
+    ///
+    /// The `block` response is of the anchored block, followed by the `eth_getBlockByNumber`
+    /// responses of its `ancestors` down to the target block.
+    pub fn encode_historical_block_proof(
+        block: Value,
+        ancestors: Vec<Value>,
+        history: Value,
+        proofs: Vec<Value>,
+        args: EthereumBlockProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        let (mut header, block) = Self::encode_header_proof(block, history)?;
+
+        let ancestors = ancestors
+            .into_iter()
+            .map(|ancestor| {
+                let (parent, bytes) = Self::encode_header(ancestor)?;
+
+                if parent.hash != header.parent_hash {
+                    return Err(EthereumError::block_header(
+                        parent.number,
+                        format_args!("not the parent of block {}", header.number),
+                    )
+                    .into());
+                }
+
+                header = parent;
+
+                Ok(bytes)
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        if header.number != args.target {
+            return Err(EthereumError::block_header(
+                header.number,
+                format_args!("expected block {}", args.target),
+            )
+            .into());
+        }
+
+        if proofs.len() != args.accounts.len() {
+            return Err(EthereumError::config(format_args!(
+                "expected {} account proofs, got {}",
+                args.accounts.len(),
+                proofs.len()
+            ))
+            .into());
+        }
+
+        if args.accounts.iter().any(|a| a.code) {
+            return Err(EthereumError::config("bytecode is only proven by state proofs").into());
+        }
+
+        let EthereumBlockProofArgs {
+            block: number,
+            root,
+            accounts,
+            payload,
+            network,
+            ..
+        } = args;
+
+        let accounts = proofs
+            .into_iter()
+            .zip(accounts)
+            .map(|(proof, args)| {
+                let proof =
+                    Self::encode_account_proof(proof, args, EthereumStorageValues::Provided, &[])?;
+
+                proof.verify(header.state_root)?;
+
+                Ok(proof)
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        let proof = EthereumBlockProof {
+            state_root: root,
+            block,
+            ancestors,
+            accounts,
+        };
+
+        let domain = network.domain();
+
+        Ok(StateProof {
+            domain,
+            state_root: root,
+            payload,
+            proof: proof.to_vec(),
+            number,
+        })
+    }
+
+    /// Encodes an `eth_getBlockByNumber` response with the `eth_getProof` response of its
+    /// EIP-2935 history slot.
+    fn encode_header_proof(
+        block: Value,
+        history: Value,
+    ) -> anyhow::Result<(Header, EthereumHeaderProof)> {
+        let (header, bytes) = Self::encode_header(block)?;
+        let hash = header.hash;
+
+        let history: EIP1186AccountProofResponse = serde_json::from_value(history)
+            .map_err(|e| EthereumError::decode("eth_getProof response", e))?;
+
+        if history.address != Address::from(Self::HISTORY_STORAGE_ADDRESS) {
+            return Err(EthereumError::block_header(
+                header.number,
+                "the history proof is not for the history contract",
+            )
+            .into());
+        }
+
+        if history
+            .storage_proof
+            .first()
+            .is_none_or(|p| p.value != U256::from_be_bytes(hash.0))
+        {
+            return Err(EthereumError::block_header(
+                header.number,
+                "the history contract doesn't hold the block hash",
+            )
+            .into());
+        }
+
+        let slot = U256::from(header.number % Self::HISTORY_SERVE_WINDOW);
+        let storage = vec![EthereumStorageProofArg {
+            key: slot.to_be_bytes::<32>().to_vec(),
+            value: Some(alloy_rlp::encode(U256::from_be_bytes(hash.0))),
+        }];
+
+        let account = EthereumAccountProofArgs {
+            address: history.address.to_string(),
+            storage,
+            mode: EthereumProofMode::Storage,
+            code: false,
+        };
+
+        let history = Self::encode_account_proof(
+            serde_json::to_value(history)?,
+            account,
+            EthereumStorageValues::Provided,
+            &[],
+        )?;
+        let proof = EthereumHeaderProof {
+            header: bytes,
+            history,
+        };
+
+        Ok((header, proof))
+    }
+
+    /// Encodes the `eth_getBlockByNumber` response of the validated block into its RLP header,
+    /// verified in the circuit by `Ethereum::verify_validated_header`.
+    pub fn encode_validated_header(block: Value) -> anyhow::Result<Vec<u8>> {
+        Self::encode_header(block).map(|(_, bytes)| bytes)
+    }
+
+    /// RLP encodes the header of an `eth_getBlockByNumber` response, checking its hash.
+    fn encode_header(block: Value) -> anyhow::Result<(Header, Vec<u8>)> {
+        let header: Header = serde_json::from_value(block)
+            .map_err(|e| EthereumError::decode("eth_getBlockByNumber response", e))?;
+        let bytes = alloy_rlp::encode(&header.inner);
+
+        if alloy_primitives::keccak256(&bytes) != header.hash {
+            return Err(EthereumError::block_header(
+                header.number,
+                "the header doesn't match the block hash",
+            )
+            .into());
+        }
+
+        Ok((header, bytes))
+    }
+
+    /// Strips the RPC metadata from the logs of a receipt.
+    fn primitive_receipt(receipt: ReceiptEnvelope<Log>) -> anyhow::Result<ReceiptEnvelope> {
+        let strip = |r: ReceiptWithBloom<Receipt<Log>>| ReceiptWithBloom {
+            receipt: Receipt {
+                status: r.receipt.status,
+                cumulative_gas_used: r.receipt.cumulative_gas_used,
+                logs: r.receipt.logs.into_iter().map(|l| l.inner).collect(),
+            },
+            logs_bloom: r.logs_bloom,
+        };
+
+        Ok(match receipt {
+            ReceiptEnvelope::Legacy(r) => ReceiptEnvelope::Legacy(strip(r)),
+            ReceiptEnvelope::Eip2930(r) => ReceiptEnvelope::Eip2930(strip(r)),
+            ReceiptEnvelope::Eip1559(r) => ReceiptEnvelope::Eip1559(strip(r)),
+            ReceiptEnvelope::Eip4844(r) => ReceiptEnvelope::Eip4844(strip(r)),
+            ReceiptEnvelope::Eip7702(r) => ReceiptEnvelope::Eip7702(strip(r)),
+            _ => return Err(EthereumError::decode("receipt", "unsupported receipt type").into()),
+        })
+    }
+
+    /// Builds a trie keyed by the RLP encoded indexes of the values, as the receipts and
+    /// transactions tries of a block.
+    ///
+    /// Returns the trie root and the proofs of the `targets` indexes.
+    fn ordered_trie_proofs(
+        values: &[Vec<u8>],
+        targets: &[u64],
+    ) -> anyhow::Result<(B256, Vec<Vec<Vec<u8>>>)> {
+        let targets = targets
+            .iter()
+            .map(|i| {
+                if (*i as usize) >= values.len() {
+                    return Err(EthereumError::InclusionProof {
+                        index: *i,
+                        reason: format!("out of bounds for {} entries", values.len()),
+                    }
+                    .into());
+                }
+
+                Ok(Nibbles::unpack(alloy_rlp::encode(*i)))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let leaves = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (Nibbles::unpack(alloy_rlp::encode(i)), value))
+            .collect();
+
+        Ok(Self::trie_proofs(leaves, targets))
+    }
+
+    /// Builds a trie from its leaves, returning the trie root and the proofs of the `targets`
+    /// keys.
+    pub fn trie_proofs<V>(
+        mut leaves: Vec<(Nibbles, V)>,
+        targets: Vec<Nibbles>,
+    ) -> (B256, Vec<Vec<Vec<u8>>>)
+    where
+        V: AsRef<[u8]>,
+    {
+        leaves.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut builder =
+            HashBuilder::default().with_proof_retainer(ProofRetainer::new(targets.clone()));
+
+        for (key, value) in leaves {
+            builder.add_leaf(key, value.as_ref());
+        }
+
+        let root = builder.root();
+        let nodes = builder.take_proof_nodes();
+
+        // nodes shorter than a hash are embedded in their parent, and not part of the proof
+        let proofs = targets
+            .iter()
+            .map(|t| {
+                nodes
+                    .matching_nodes_sorted(t)
+                    .into_iter()
+                    .filter(|(path, node)| path.is_empty() || node.len() >= B256::len_bytes())
+                    .map(|(_, node)| node.to_vec())
+                    .collect()
+            })
+            .collect();
+
+        (root, proofs)
+    }
+
+    /// Encodes the `eth_getCode` response of a proven account, checking it against its code hash.
+    fn encode_code_proof(
+        account: &EthereumAccountProof,
+        code: Value,
+    ) -> anyhow::Result<EthereumCodeProof> {
+        let code: Bytes = serde_json::from_value(code)
+            .map_err(|e| EthereumError::decode("eth_getCode response", e))?;
+
+        // a non-existent account is proven with the hash of the empty code
+        let code_hash = match account.exists {
+            true => account.code_hash,
+            false => KECCAK_EMPTY.0,
+        };
+
+        let proof = EthereumCodeProof {
+            account: account.account.clone(),
+            code: code.to_vec(),
+        };
+
+        proof.verify(&code_hash)?;
+
+        Ok(proof)
+    }
+
+    /// Encodes the `eth_getProof` response of an account, taking the storage values from
+    /// `values`, or from the response for the `fetched` slots.
+    fn encode_account_proof(
+        proof: Value,
+        args: EthereumAccountProofArgs,
+        values: EthereumStorageValues,
+        fetched: &[Vec<u8>],
+    ) -> anyhow::Result<EthereumAccountProof> {
+        let proof: EIP1186AccountProofResponse = serde_json::from_value(proof)
+            .map_err(|e| EthereumError::decode("eth_getProof response", e))?;
         let account = proof.address.to_vec();
         let nonce = proof.nonce;
-        let balance = proof.balance.to();
+        let balance = proof.balance.to_be_bytes();
         let account_proof = proof.account_proof.iter().map(|b| b.to_vec()).collect();
 
         let storage_root = proof.storage_hash.as_slice();
         let storage_root =
-            Hash::try_from(storage_root).map_err(|_| anyhow::anyhow!("invalid storage root"))?;
+            Hash::try_from(storage_root).map_err(|e| EthereumError::decode("storage root", e))?;
 
         let code_hash = proof.code_hash.as_slice();
         let code_hash =
-            Hash::try_from(code_hash).map_err(|_| anyhow::anyhow!("invalid code hash"))?;
+            Hash::try_from(code_hash).map_err(|e| EthereumError::decode("code hash", e))?;
 
-        let EthereumStateProofArgs {
+        let EthereumAccountProofArgs {
+            address,
             storage,
-            payload,
-            root,
-            block,
+            mode,
             ..
         } = args;
 
-        let storage_proofs = proof
+        let requested: Address = address
+            .parse()
+            .map_err(|_| EthereumError::config(format_args!("invalid address `{address}`")))?;
+
+        if requested != proof.address {
+            return Err(EthereumError::AccountMismatch {
+                requested: requested.to_vec(),
+                returned: account,
+            }
+            .into());
+        }
+
+        let exists = mode != EthereumProofMode::NonExistence;
+
+        if !exists && (nonce != 0 || !proof.balance.is_zero()) {
+            return Err(EthereumError::AccountProof {
+                account,
+                reason: "the account exists at the provided block".into(),
+            }
+            .into());
+        }
+
+        let storage = match mode {
+            EthereumProofMode::Storage => storage,
+            EthereumProofMode::Account | EthereumProofMode::NonExistence => Vec::new(),
+        };
+
+        let responses: Vec<_> = proof
             .storage_proof
             .iter()
-            .zip(storage)
-            .map(|(p, arg)| {
+            .map(|p| {
                 let key = match p.key {
                     JsonStorageKey::Hash(b) => b.to_vec(),
                     JsonStorageKey::Number(n) => n.to_be_bytes::<32>().to_vec(),
                 };
+
+                (key, p)
+            })
+            .collect();
+
+        // responses are matched by key, as the RPC may drop, reorder or add slots
+        if mode == EthereumProofMode::Storage {
+            if let Some((key, _)) = responses
+                .iter()
+                .find(|(key, _)| !storage.iter().any(|s| s.key == *key))
+            {
+                return Err(EthereumError::UnexpectedSlot { key: key.clone() }.into());
+            }
+        }
+
+        let storage_proofs = storage
+            .into_iter()
+            .map(|arg| {
+                let (key, p) = responses
+                    .iter()
+                    .find(|(key, _)| *key == arg.key)
+                    .ok_or_else(|| EthereumError::MissingSlot {
+                        key: arg.key.clone(),
+                    })?;
+
+                let values = match fetched.contains(key) {
+                    true => EthereumStorageValues::Fetched,
+                    false => values,
+                };
+
+                let fetched = Ethereum::encode_slot_value(&p.value.to_be_bytes());
                 let value = arg.value.filter(|v| !v.is_empty());
+
+                let value = match values {
+                    EthereumStorageValues::Provided => value,
+                    EthereumStorageValues::Fetched => fetched,
+                    EthereumStorageValues::Checked if value == fetched => value,
+                    EthereumStorageValues::Checked => {
+                        return Err(EthereumError::ValueMismatch { key: key.clone() }.into())
+                    }
+                };
+
                 let proof = p.proof.iter().map(|b| b.to_vec()).collect();
 
-                EthereumStorageProof { key, value, proof }
+                Ok(EthereumStorageProof {
+                    key: key.clone(),
+                    value,
+                    proof,
+                })
             })
-            .collect();
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(EthereumAccountProof {
+            account,
+            exists,
+            nonce,
+            balance,
+            storage_root,
+            code_hash,
+            account_proof,
+            storage_proofs,
+        })
+    }
+}
+
+impl EthereumStateProofArgs {
+    /// Returns the primary account followed by the additional accounts.
+    ///
+    /// The [Self::fetched] slots missing from the storage of the primary account are appended to
+    /// it.
+    pub fn to_accounts(&self) -> Vec<EthereumAccountProofArgs> {
+        let fetched = self
+            .fetched
+            .iter()
+            .filter(|key| !self.storage.iter().any(|s| s.key == **key))
+            .map(|key| EthereumStorageProofArg {
+                key: key.clone(),
+                value: None,
+            });
+
+        let primary = EthereumAccountProofArgs {
+            address: self.address.clone(),
+            storage: self.storage.iter().cloned().chain(fetched).collect(),
+            mode: self.mode,
+            code: self.code,
+        };
+
+        iter::once(primary)
+            .chain(self.accounts.iter().cloned())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthereumStorageLayoutBuilder {
+    storage: Vec<EthereumStorageProofArg>,
+    base: U256,
+}
+
+impl EthereumStorageLayoutBuilder {
+    pub fn new(base_slot: u64) -> Self {
+        Self::new_with_slot(U256::from(base_slot))
+    }
+
+    /// Starts at an arbitrary 32 bytes `base_slot`.
+    pub fn new_with_slot(base_slot: U256) -> Self {
+        Self {
+            storage: Vec::with_capacity(10),
+            base: base_slot,
+        }
+    }
+
+    /// Starts at the base slot of the ERC-7201 namespace `id`, such as
+    /// `openzeppelin.storage.ERC20`.
+    pub fn new_namespace(id: &str) -> Self {
+        Self::new_with_slot(Ethereum::erc7201_slot(id))
+    }
+
+    pub fn new_mapping(mapping_id: u64, base_slot: u64) -> Self {
+        Self::new_mapping_with_key(mapping_id, base_slot)
+    }
+
+    /// Starts at the entry of `key` in the mapping stored at `base_slot`.
+    pub fn new_mapping_with_key<K>(key: K, base_slot: u64) -> Self
+    where
+        K: Into<EthereumMappingKey>,
+    {
+        Self::new(base_slot).enter_mapping(key)
+    }
+
+    /// Moves to the entry of `key` in the mapping stored at the current slot.
+    ///
+    /// Can be chained for nested mappings, such as `allowance[owner][spender]`.
+    pub fn enter_mapping<K>(mut self, key: K) -> Self
+    where
+        K: Into<EthereumMappingKey>,
+    {
+        self.base = key.into().slot(self.base);
+        self
+    }
+
+    fn next_slot_entry(&mut self) -> Vec<u8> {
+        let key = self.base.to_be_bytes::<32>();
+        self.base += U256::ONE;
+        key.to_vec()
+    }
+
+    /// Adds combined values into a single slot.
+    ///
+    /// The values are concatenated as-is, so they must be given from the highest-order bytes of
+    /// the slot. See [Self::add_packed_values] to pack fields in declaration order.
+    pub fn add_combined_values<I, R>(self, items: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[u8]>,
+    {
+        let mut value = Vec::with_capacity(32);
+
+        for i in items {
+            value.extend(i.as_ref());
+        }
+
+        self.add_value(value)
+    }
+
+    /// Adds fields packed from the current slot, each declared with its size in bytes.
+    ///
+    /// Fields are given in declaration order, and packed as Solidity does: from the lowest-order
+    /// bytes of a slot, starting a new slot when a field doesn't fit the remainder.
+    pub fn add_packed_values<I, R>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, R)>,
+        R: AsRef<[u8]>,
+    {
+        let (sizes, values): (Vec<_>, Vec<_>) = fields.into_iter().unzip();
+        let layout = EthereumPackedLayout::new(sizes);
+
+        self.push_fields(layout.positions(self.base).into_iter().zip(values));
+        self.base += U256::from(layout.slots());
+        self
+    }
+
+    /// Adds a value from the current slot, laid out as Solidity does.
+    ///
+    /// Structs implement [EthereumStorageLayout] with `#[derive(StorageLayout)]`.
+    pub fn add_layout<T>(mut self, value: &T) -> Self
+    where
+        T: EthereumStorageLayout,
+    {
+        self.storage.extend(value.to_storage(self.base));
+        self.base += U256::from(T::slots());
+        self
+    }
+
+    /// Adds a single entry
+    ///
+    /// Only for types that fit into a single slot and don't have a special encoding (e.g. strings, bytes...)
+    pub fn add_value<T>(mut self, value: T) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let value = value.as_ref();
+        let key = self.next_slot_entry();
+
+        let value = Some(rlp::encode(&value).to_vec());
+        self.storage.push(EthereumStorageProofArg { key, value });
+        self
+    }
+
+    /// Adds a single string entry.
+    ///
+    /// Will consume multiple slots if the length is greater or equal than 32.
+    pub fn add_string_value<T>(mut self, value: T) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let value = value.as_ref();
+        let len = value.len() as u64;
+
+        if len < 32 {
+            let key = self.next_slot_entry();
+            let mut slot_value = [0u8; 32];
+            slot_value[..len as usize].copy_from_slice(value); // Left-align the data
+            slot_value[31] = (len * 2) as u8; // Length * 2 in rightmost byte
+
+            let value = Some(rlp::encode(&slot_value.to_vec()).to_vec());
+            self.storage.push(EthereumStorageProofArg { key, value });
+        } else {
+            let key = self.base;
+            self.base += U256::ONE;
+
+            let base_slot = (len << 1) + 1;
+            let base_slot = U256::from(base_slot).to_be_bytes_trimmed_vec();
+
+            self.storage.push(EthereumStorageProofArg {
+                key: key.to_be_bytes::<32>().to_vec(),
+                value: Some(rlp::encode(&base_slot).to_vec()),
+            });
+
+            let base_slot = alloy_primitives::keccak256(key.to_be_bytes::<32>());
+            let base_slot = U256::from_be_slice(base_slot.as_slice());
+
+            for (i, c) in value.chunks(32).enumerate() {
+                let i = U256::from(i);
+                let mut value = c.to_vec();
+
+                value.resize(32, 0);
+
+                self.storage.push(EthereumStorageProofArg {
+                    key: (base_slot + i).to_be_bytes::<32>().to_vec(),
+                    value: Some(rlp::encode(&value).to_vec()),
+                });
+            }
+        }
+
+        self
+    }
+
+    /// Adds a `T[]` array at the current slot, with its length and `element_size` bytes elements.
+    ///
+    /// The elements are packed at the data slots of the array. For arrays of structs, see
+    /// [Self::enter_dynamic_array].
+    pub fn add_dynamic_array<I, R>(mut self, element_size: usize, items: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[u8]>,
+    {
+        let items: Vec<_> = items.into_iter().collect();
+        let key = self.next_slot_entry();
+
+        let layout = EthereumArrayLayout::dynamic(U256::from_be_slice(&key), element_size);
+        let value = U256::from(items.len()).to_be_bytes::<32>();
+        let value = Ethereum::encode_slot_value(&value);
+
+        self.storage.push(EthereumStorageProofArg { key, value });
+        self.push_array_elements(layout, items);
+        self
+    }
+
+    /// Adds a `T[N]` array of `element_size` bytes elements, packed from the current slot.
+    pub fn add_fixed_array<I, R>(mut self, element_size: usize, items: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[u8]>,
+    {
+        let items: Vec<_> = items.into_iter().collect();
+        let layout = EthereumArrayLayout::fixed(self.base, element_size);
+
+        self.base += U256::from(layout.slots(items.len() as u64));
+        self.push_array_elements(layout, items);
+        self
+    }
+
+    /// Moves to the element at `index` of the `T[]` array stored at the current slot.
+    ///
+    /// Elements of `element_size` bytes larger than a slot, such as structs, can then be added
+    /// field by field.
+    pub fn enter_dynamic_array(mut self, element_size: usize, index: u64) -> Self {
+        self.base = EthereumArrayLayout::dynamic(self.base, element_size)
+            .position(index)
+            .slot();
+        self
+    }
+
+    /// Moves to the element at `index` of the `T[N]` array starting at the current slot.
+    pub fn enter_fixed_array(mut self, element_size: usize, index: u64) -> Self {
+        self.base = EthereumArrayLayout::fixed(self.base, element_size)
+            .position(index)
+            .slot();
+        self
+    }
+
+    fn push_array_elements<R>(&mut self, layout: EthereumArrayLayout, items: Vec<R>)
+    where
+        R: AsRef<[u8]>,
+    {
+        let positions = (0..items.len() as u64).map(|i| layout.position(i));
+
+        self.push_fields(positions.zip(items));
+    }
+
+    /// Packs the values into their slots, pushing each slot once.
+    fn push_fields<I, R>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = (EthereumSlotPosition, R)>,
+        R: AsRef<[u8]>,
+    {
+        let mut slot: Option<(U256, [u8; 32])> = None;
+
+        for (position, value) in fields {
+            if let Some((key, word)) = slot.take_if(|(key, _)| *key != position.slot()) {
+                self.push_slot(key, &word);
+            }
+
+            let (_, word) = slot.get_or_insert((position.slot(), [0; 32]));
+
+            position.write(word, value.as_ref());
+        }
+
+        if let Some((key, word)) = slot {
+            self.push_slot(key, &word);
+        }
+    }
+
+    fn push_slot(&mut self, key: U256, word: &[u8; 32]) {
+        self.storage.push(EthereumStorageProofArg {
+            key: key.to_be_bytes::<32>().to_vec(),
+            value: Ethereum::encode_slot_value(word),
+        });
+    }
+
+    /// Adds the proxy `slot` holding `address`, without moving the current slot.
+    pub fn add_proxy_slot(mut self, slot: EthereumProxySlot, address: Address) -> Self {
+        self.push_slot(slot.slot(), &B256::left_padding_from(address.as_slice()).0);
+        self
+    }
+
+    /// Adds an empty slot for proof of non-membership.
+    pub fn add_empty_slot(mut self) -> Self {
+        let key = self.next_slot_entry();
+        let value = None;
+
+        self.storage.push(EthereumStorageProofArg { key, value });
+
+        self
+    }
+
+    pub fn build(self) -> Vec<EthereumStorageProofArg> {
+        self.storage
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthereumStateProofArgsBuilder {
+    address: String,
+    block: Option<(u64, Hash)>,
+    storage: Vec<EthereumStorageProofArg>,
+    payload: Vec<u8>,
+    mode: EthereumProofMode,
+    accounts: Vec<EthereumAccountProofArgs>,
+    compact: bool,
+    values: EthereumStorageValues,
+    network: EthereumNetwork,
+    provider: Option<EthereumProviderConfig>,
+    code: bool,
+    fetched: Vec<Vec<u8>>,
+}
+
+impl EthereumStateProofArgsBuilder {
+    pub fn new(address: String) -> Self {
+        Self {
+            address,
+            block: None,
+            storage: Default::default(),
+            payload: Default::default(),
+            mode: Default::default(),
+            accounts: Default::default(),
+            compact: false,
+            values: Default::default(),
+            network: Default::default(),
+            provider: None,
+            code: false,
+            fetched: Vec::new(),
+        }
+    }
+
+    pub fn with_block(mut self, number: u64, root: Hash) -> Self {
+        self.block.replace((number, root));
+        self
+    }
+
+    pub fn with_storage(mut self, storage: Vec<EthereumStorageProofArg>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn with_payload(mut self, payload: Vec<u8>) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: EthereumProofMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Adds the proxy `slots` of the account, kept apart from [Self::with_storage].
+    ///
+    /// Their values are unknown, so they are always taken from the `eth_getProof` response,
+    /// whatever the [Self::with_values] source. Known addresses are added with
+    /// [EthereumStorageLayoutBuilder::add_proxy_slot].
+    pub fn with_proxy_slots<I>(mut self, slots: I) -> Self
+    where
+        I: IntoIterator<Item = EthereumProxySlot>,
+    {
+        self.fetched
+            .extend(slots.into_iter().map(|slot| slot.key().to_vec()));
+        self
+    }
+
+    /// Proves the bytecode of the account, fetched with `eth_getCode`.
+    pub fn with_code(mut self, code: bool) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Proves the `slot` of the account committing to the payload with its keccak hash, checked
+    /// in the circuit by [crate::EthereumProvenAccount::verify_payload].
+    ///
+    /// The value is always taken from the `eth_getProof` response, whatever the
+    /// [Self::with_values] source.
+    pub fn with_payload_commitment(mut self, slot: U256) -> Self {
+        self.fetched.push(slot.to_be_bytes::<32>().to_vec());
+        self
+    }
+
+    /// Adds an account to be proven at the same block.
+    pub fn with_account(mut self, account: EthereumAccountProofArgs) -> Self {
+        self.accounts.push(account);
+        self
+    }
+
+    /// Deduplicates the trie nodes of the proof, reducing the witness size.
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Sets the source of the proven storage values.
+    pub fn with_values(mut self, values: EthereumStorageValues) -> Self {
+        self.values = values;
+        self
+    }
+
+    /// Sets the network of the proof.
+    pub fn with_network(mut self, network: EthereumNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sets the endpoint of the controller requests.
+    pub fn with_provider(mut self, provider: EthereumProviderConfig) -> Self {
+        self.provider.replace(provider);
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<Value> {
+        let (block, root) = match self.block {
+            Some(x) => x,
+            None => Ethereum::get_network_latest_block(self.network).map(|b| (b.number, b.root))?,
+        };
 
-        let proof = EthereumStateProof {
-            state_root: args.root,
-            account,
-            nonce,
-            balance,
-            storage_root,
-            code_hash,
-            account_proof,
-            storage_proofs,
-        }
-        .pack_to_vec();
+        let mut args = serde_json::to_value(EthereumStateProofArgs {
+            address: self.address,
+            block,
+            root,
+            storage: self.storage,
+            payload: self.payload,
+            network: self.network,
+            mode: self.mode,
+            accounts: self.accounts,
+            compact: self.compact,
+            values: self.values,
+            code: self.code,
+            fetched: self.fetched,
+        })?;
 
-        let domain = DomainData::identifier_from_parts(Self::ID);
-        let state_root = root;
+        if let Some(provider) = self.provider {
+            provider.apply(&mut args)?;
+        }
 
-        Ok(StateProof {
-            domain,
-            state_root,
-            payload,
-            proof,
-            number: block,
-        })
+        Ok(args)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct EthereumStorageLayoutBuilder {
-    storage: Vec<EthereumStorageProofArg>,
-    base: U256,
+pub struct EthereumReceiptProofArgsBuilder {
+    block: Option<(u64, Hash)>,
+    target: u64,
+    transactions: Vec<u64>,
+    payload: Vec<u8>,
+    network: EthereumNetwork,
+    provider: Option<EthereumProviderConfig>,
 }
 
-impl EthereumStorageLayoutBuilder {
-    pub fn new(base_slot: u64) -> Self {
+impl EthereumReceiptProofArgsBuilder {
+    /// Creates the arguments to prove receipts of the `target` block.
+    pub fn new(target: u64) -> Self {
         Self {
-            storage: Vec::with_capacity(10),
-            base: U256::from(base_slot),
+            block: None,
+            target,
+            transactions: Default::default(),
+            payload: Default::default(),
+            network: Default::default(),
+            provider: None,
         }
     }
 
-    pub fn new_mapping(mapping_id: u64, base_slot: u64) -> Self {
-        let id = U256::from(mapping_id).to_be_bytes::<32>();
-        let slot = U256::from(base_slot).to_be_bytes::<32>();
-        let slot = [id, slot].concat();
-        let slot = alloy_primitives::keccak256(slot);
-
-        Self {
-            storage: Vec::with_capacity(10),
-            base: U256::from_be_slice(slot.as_slice()),
-        }
+    pub fn with_block(mut self, number: u64, root: Hash) -> Self {
+        self.block.replace((number, root));
+        self
     }
 
-    fn next_slot_entry(&mut self) -> Vec<u8> {
-        let key = self.base.to_be_bytes::<32>();
-        self.base += U256::ONE;
-        key.to_vec()
+    /// Adds the receipt of the transaction at `index` in the target block.
+    pub fn with_transaction(mut self, index: u64) -> Self {
+        self.transactions.push(index);
+        self
     }
 
-    /// Adds combined values into a single slot.
-    pub fn add_combined_values<I, R>(self, items: I) -> Self
-    where
-        I: IntoIterator<Item = R>,
-        R: AsRef<[u8]>,
-    {
-        let mut value = Vec::with_capacity(32);
-
-        for i in items {
-            value.extend(i.as_ref());
-        }
-
-        self.add_value(value)
+    pub fn with_payload(mut self, payload: Vec<u8>) -> Self {
+        self.payload = payload;
+        self
     }
 
-    /// Adds a single entry
-    ///
-    /// Only for types that fit into a single slot and don't have a special encoding (e.g. strings, bytes...)
-    pub fn add_value<T>(mut self, value: T) -> Self
-    where
-        T: AsRef<[u8]>,
-    {
-        let value = value.as_ref();
-        let key = self.next_slot_entry();
-
-        let value = Some(rlp::encode(&value).to_vec());
-        self.storage.push(EthereumStorageProofArg { key, value });
+    /// Sets the network of the proof.
+    pub fn with_network(mut self, network: EthereumNetwork) -> Self {
+        self.network = network;
         self
     }
 
-    /// Adds a single string entry.
-    ///
-    /// Will consume multiple slots if the length is greater or equal than 32.
-    pub fn add_string_value<T>(mut self, value: T) -> Self
-    where
-        T: AsRef<[u8]>,
-    {
-        let value = value.as_ref();
-        let len = value.len() as u64;
+    /// Sets the endpoint of the controller requests.
+    pub fn with_provider(mut self, provider: EthereumProviderConfig) -> Self {
+        self.provider.replace(provider);
+        self
+    }
 
-        if len < 32 {
-            let key = self.next_slot_entry();
-            let mut slot_value = [0u8; 32];
-            slot_value[..len as usize].copy_from_slice(value); // Left-align the data
-            slot_value[31] = (len * 2) as u8; // Length * 2 in rightmost byte
+    pub fn build(self) -> anyhow::Result<Value> {
+        let (block, root) = match self.block {
+            Some(x) => x,
+            None => Ethereum::get_network_latest_block(self.network).map(|b| (b.number, b.root))?,
+        };
 
-            let value = Some(rlp::encode(&slot_value.to_vec()).to_vec());
-            self.storage.push(EthereumStorageProofArg { key, value });
-        } else {
-            let key = self.base;
-            self.base += U256::ONE;
+        let mut args =
+            serde_json::to_value(EthereumProofArgs::Receipt(EthereumReceiptProofArgs {
+                block,
+                root,
+                target: self.target,
+                transactions: self.transactions,
+                payload: self.payload,
+                network: self.network,
+            }))?;
 
-            let base_slot = (len << 1) + 1;
-            let base_slot = U256::from(base_slot).to_be_bytes_trimmed_vec();
+        if let Some(provider) = self.provider {
+            provider.apply(&mut args)?;
+        }
 
-            self.storage.push(EthereumStorageProofArg {
-                key: key.to_be_bytes::<32>().to_vec(),
-                value: Some(rlp::encode(&base_slot).to_vec()),
-            });
+        Ok(args)
+    }
+}
 
-            let base_slot = alloy_primitives::keccak256(key.to_be_bytes::<32>());
-            let base_slot = U256::from_be_slice(base_slot.as_slice());
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthereumTransactionProofArgsBuilder {
+    block: Option<(u64, Hash)>,
+    target: u64,
+    transactions: Vec<u64>,
+    hashes: Vec<Hash>,
+    payload: Vec<u8>,
+    network: EthereumNetwork,
+    provider: Option<EthereumProviderConfig>,
+}
 
-            for (i, c) in value.chunks(32).enumerate() {
-                let i = U256::from(i);
-                let mut value = c.to_vec();
+impl EthereumTransactionProofArgsBuilder {
+    /// Creates the arguments to prove transactions of the `target` block.
+    pub fn new(target: u64) -> Self {
+        Self {
+            block: None,
+            target,
+            transactions: Default::default(),
+            hashes: Default::default(),
+            payload: Default::default(),
+            network: Default::default(),
+            provider: None,
+        }
+    }
 
-                value.resize(32, 0);
+    pub fn with_block(mut self, number: u64, root: Hash) -> Self {
+        self.block.replace((number, root));
+        self
+    }
 
-                self.storage.push(EthereumStorageProofArg {
-                    key: (base_slot + i).to_be_bytes::<32>().to_vec(),
-                    value: Some(rlp::encode(&value).to_vec()),
-                });
-            }
-        }
+    /// Adds the transaction at `index` in the target block.
+    pub fn with_transaction(mut self, index: u64) -> Self {
+        self.transactions.push(index);
+        self
+    }
 
+    /// Adds a transaction of the target block by its hash.
+    pub fn with_transaction_hash(mut self, hash: Hash) -> Self {
+        self.hashes.push(hash);
         self
     }
 
-    /// Adds an empty slot for proof of non-membership.
-    pub fn add_empty_slot(mut self) -> Self {
-        let key = self.next_slot_entry();
-        let value = None;
+    pub fn with_payload(mut self, payload: Vec<u8>) -> Self {
+        self.payload = payload;
+        self
+    }
 
-        self.storage.push(EthereumStorageProofArg { key, value });
+    /// Sets the network of the proof.
+    pub fn with_network(mut self, network: EthereumNetwork) -> Self {
+        self.network = network;
+        self
+    }
 
+    /// Sets the endpoint of the controller requests.
+    pub fn with_provider(mut self, provider: EthereumProviderConfig) -> Self {
+        self.provider.replace(provider);
         self
     }
 
-    pub fn build(self) -> Vec<EthereumStorageProofArg> {
-        self.storage
+    pub fn build(self) -> anyhow::Result<Value> {
+        let (block, root) = match self.block {
+            Some(x) => x,
+            None => Ethereum::get_network_latest_block(self.network).map(|b| (b.number, b.root))?,
+        };
+
+        let mut args = serde_json::to_value(EthereumProofArgs::Transaction(
+            EthereumTransactionProofArgs {
+                block,
+                root,
+                target: self.target,
+                transactions: self.transactions,
+                hashes: self.hashes,
+                payload: self.payload,
+                network: self.network,
+            },
+        ))?;
+
+        if let Some(provider) = self.provider {
+            provider.apply(&mut args)?;
+        }
+
+        Ok(args)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct EthereumStateProofArgsBuilder {
-    address: String,
+pub struct EthereumBlockProofArgsBuilder {
     block: Option<(u64, Hash)>,
-    storage: Vec<EthereumStorageProofArg>,
+    target: u64,
+    accounts: Vec<EthereumAccountProofArgs>,
     payload: Vec<u8>,
+    network: EthereumNetwork,
+    provider: Option<EthereumProviderConfig>,
 }
 
-impl EthereumStateProofArgsBuilder {
-    pub fn new(address: String) -> Self {
+impl EthereumBlockProofArgsBuilder {
+    /// Creates the arguments to prove the header of the `target` block.
+    pub fn new(target: u64) -> Self {
         Self {
-            address,
             block: None,
-            storage: Default::default(),
+            target,
+            accounts: Default::default(),
             payload: Default::default(),
+            network: Default::default(),
+            provider: None,
         }
     }
 
@@ -234,8 +1404,9 @@ impl EthereumStateProofArgsBuilder {
         self
     }
 
-    pub fn with_storage(mut self, storage: Vec<EthereumStorageProofArg>) -> Self {
-        self.storage = storage;
+    /// Adds an account to be proven at the target block.
+    pub fn with_account(mut self, account: EthereumAccountProofArgs) -> Self {
+        self.accounts.push(account);
         self
     }
 
@@ -244,41 +1415,296 @@ impl EthereumStateProofArgsBuilder {
         self
     }
 
+    /// Sets the network of the proof.
+    pub fn with_network(mut self, network: EthereumNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sets the endpoint of the controller requests.
+    pub fn with_provider(mut self, provider: EthereumProviderConfig) -> Self {
+        self.provider.replace(provider);
+        self
+    }
+
     pub fn build(self) -> anyhow::Result<Value> {
         let (block, root) = match self.block {
             Some(x) => x,
-            None => Ethereum::get_latest_block().map(|b| (b.number, b.root))?,
+            None => Ethereum::get_network_latest_block(self.network).map(|b| (b.number, b.root))?,
         };
 
-        Ok(serde_json::to_value(EthereumStateProofArgs {
-            address: self.address,
+        let mut args = serde_json::to_value(EthereumProofArgs::Block(EthereumBlockProofArgs {
             block,
             root,
-            storage: self.storage,
+            target: self.target,
+            accounts: self.accounts,
             payload: self.payload,
-        })?)
+            network: self.network,
+        }))?;
+
+        if let Some(provider) = self.provider {
+            provider.apply(&mut args)?;
+        }
+
+        Ok(args)
     }
 }
 
-impl DomainController for Ethereum {
-    const ID: &str = Self::ID;
+/// A JSON-RPC endpoint serving the execution layer requests of the controller.
+pub trait EthereumProvider {
+    /// Returns the result of the JSON-RPC `method` called with `params`.
+    fn request(&self, method: &str, params: &Value) -> anyhow::Result<Value>;
+}
 
-    fn state_proof(&self, args: Value) -> anyhow::Result<StateProof> {
-        let args: EthereumStateProofArgs = serde_json::from_value(args)?;
+/// Endpoint of the controller requests, read from the `provider` field of the proof arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EthereumProviderConfig {
+    /// Alchemy endpoint of the co-processor for `network`.
+    Alchemy { network: String },
+
+    /// JSON-RPC endpoint at `url`, reached via the co-processor HTTP call.
+    Http { url: String },
+}
+
+impl Default for EthereumProviderConfig {
+    fn default() -> Self {
+        EthereumNetwork::default().into()
+    }
+}
+
+impl From<EthereumNetwork> for EthereumProviderConfig {
+    fn from(network: EthereumNetwork) -> Self {
+        Self::Alchemy {
+            network: network.alchemy().into(),
+        }
+    }
+}
+
+impl EthereumProviderConfig {
+    /// Reads the provider of the proof arguments, defaulting to the Alchemy endpoint of their
+    /// network.
+    pub fn from_args(args: &Value) -> anyhow::Result<Self> {
+        match (args.get("provider"), args.get("network")) {
+            (Some(provider), _) => {
+                Ok(serde_json::from_value(provider.clone()).map_err(EthereumError::config)?)
+            }
+            (None, Some(network)) => Ok(Self::from(
+                serde_json::from_value::<EthereumNetwork>(network.clone())
+                    .map_err(EthereumError::config)?,
+            )),
+            (None, None) => Ok(Self::default()),
+        }
+    }
+
+    /// Sets the provider of the proof arguments.
+    pub fn apply(&self, args: &mut Value) -> anyhow::Result<()> {
+        args.as_object_mut()
+            .ok_or_else(|| EthereumError::config("the proof arguments are not an object"))?
+            .insert("provider".into(), serde_json::to_value(self)?);
+
+        Ok(())
+    }
+}
+
+impl EthereumProvider for EthereumProviderConfig {
+    fn request(&self, method: &str, params: &Value) -> anyhow::Result<Value> {
+        match self {
+            Self::Alchemy { network } => abi::alchemy(network, method, params),
+            Self::Http { url } => {
+                let response = abi::http(&json!({
+                    "url": url,
+                    "method": "post",
+                    "headers": { "content-type": "application/json" },
+                    "json": {
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "method": method,
+                        "params": params,
+                    },
+                }))?;
+
+                let error = |reason: String| EthereumError::Rpc {
+                    method: method.into(),
+                    reason,
+                };
+
+                if let Some(e) = response.get("error") {
+                    return Err(error(e.to_string()).into());
+                }
+
+                Ok(response
+                    .get("result")
+                    .cloned()
+                    .ok_or_else(|| error("no result".into()))?)
+            }
+        }
+    }
+}
+
+/// Serves recorded responses, so the controller runs offline.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EthereumMemoryProvider {
+    responses: Vec<(String, Value, Value)>,
+}
+
+impl EthereumMemoryProvider {
+    /// Returns `response` to the requests of `method` with `params`.
+    pub fn with_response(mut self, method: &str, params: Value, response: Value) -> Self {
+        self.responses.push((method.into(), params, response));
+        self
+    }
+}
+
+impl EthereumProvider for EthereumMemoryProvider {
+    fn request(&self, method: &str, params: &Value) -> anyhow::Result<Value> {
+        self.responses
+            .iter()
+            .find(|(m, p, _)| m == method && p == params)
+            .map(|(_, _, response)| response.clone())
+            .ok_or_else(|| {
+                EthereumError::Rpc {
+                    method: method.into(),
+                    reason: format!("no response recorded for {params}"),
+                }
+                .into()
+            })
+    }
+}
+
+impl Ethereum {
+    /// Computes the state proof of `args` with the requests served by `provider`.
+    pub fn state_proof_with(
+        provider: &dyn EthereumProvider,
+        args: Value,
+    ) -> anyhow::Result<StateProof> {
+        let args = match args.get("kind") {
+            Some(_) => serde_json::from_value(args).map_err(EthereumError::config)?,
+            None => EthereumProofArgs::State(
+                serde_json::from_value(args).map_err(EthereumError::config)?,
+            ),
+        };
+
+        match args {
+            EthereumProofArgs::State(args) => Self::fetch_state_proof(provider, args),
+            EthereumProofArgs::Receipt(args) => Self::fetch_receipt_proof(provider, args),
+            EthereumProofArgs::Transaction(args) => Self::fetch_transaction_proof(provider, args),
+            EthereumProofArgs::Block(args) => Self::fetch_block_proof(provider, args),
+        }
+    }
+
+    fn fetch_state_proof(
+        provider: &dyn EthereumProvider,
+        args: EthereumStateProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        let accounts = args.to_accounts();
+        let proofs = Self::fetch_accounts(provider, &accounts, args.block)?;
         let block = U256::from(args.block);
+        let codes = accounts
+            .iter()
+            .filter(|account| account.code)
+            .map(|account| provider.request("eth_getCode", &json!([account.address, block])))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ethereum::encode_proofs_with_codes(proofs, codes, args)
+    }
+
+    /// Fetches the `eth_getProof` responses of the accounts at `block`.
+    fn fetch_accounts(
+        provider: &dyn EthereumProvider,
+        accounts: &[EthereumAccountProofArgs],
+        block: u64,
+    ) -> anyhow::Result<Vec<Value>> {
+        let block = U256::from(block);
 
-        let storage_keys: Vec<_> = args
-            .storage
+        accounts
             .iter()
-            .map(|s| U256::from_be_slice(s.key.as_slice()))
-            .collect();
+            .map(|account| {
+                let storage_keys: Vec<_> = match account.mode {
+                    EthereumProofMode::Storage => account
+                        .storage
+                        .iter()
+                        .map(|s| U256::from_be_slice(s.key.as_slice()))
+                        .collect(),
+                    EthereumProofMode::Account | EthereumProofMode::NonExistence => Vec::new(),
+                };
 
-        let proof = abi::alchemy(
-            Self::NETWORK,
-            "eth_getProof",
-            &json!([args.address, storage_keys, block]),
-        )?;
+                provider.request(
+                    "eth_getProof",
+                    &json!([account.address, storage_keys, block]),
+                )
+            })
+            .collect()
+    }
+
+    fn fetch_receipt_proof(
+        provider: &dyn EthereumProvider,
+        args: EthereumReceiptProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        let target = U256::from(args.target);
+        let (block, history) = Self::fetch_header(provider, args.target, args.block, false)?;
+        let receipts = provider.request("eth_getBlockReceipts", &json!([target]))?;
+
+        Self::encode_receipt_proof(block, receipts, history, args)
+    }
+
+    fn fetch_transaction_proof(
+        provider: &dyn EthereumProvider,
+        args: EthereumTransactionProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        let (block, history) = Self::fetch_header(provider, args.target, args.block, true)?;
+
+        Self::encode_transaction_proof(block, history, args)
+    }
+
+    fn fetch_block_proof(
+        provider: &dyn EthereumProvider,
+        args: EthereumBlockProofArgs,
+    ) -> anyhow::Result<StateProof> {
+        // the oldest block of the history window is the closest anchor to older targets
+        let anchor = args
+            .target
+            .max(args.block.saturating_sub(Self::HISTORY_SERVE_WINDOW));
+
+        let (block, history) = Self::fetch_header(provider, anchor, args.block, false)?;
+        let ancestors = (args.target..anchor)
+            .rev()
+            .map(|n| provider.request("eth_getBlockByNumber", &json!([U256::from(n), false])))
+            .collect::<anyhow::Result<_>>()?;
+
+        let proofs = Self::fetch_accounts(provider, &args.accounts, args.target)?;
+
+        Self::encode_historical_block_proof(block, ancestors, history, proofs, args)
+    }
+
+    /// Fetches the `target` block with the proof of its history slot at the validated `block`.
+    ///
+    /// The block includes its full transactions if `full` is set.
+    fn fetch_header(
+        provider: &dyn EthereumProvider,
+        target: u64,
+        block: u64,
+        full: bool,
+    ) -> anyhow::Result<(Value, Value)> {
+        let address = Address::from(Self::HISTORY_STORAGE_ADDRESS);
+        let slot = U256::from(target % Self::HISTORY_SERVE_WINDOW);
+
+        let header =
+            provider.request("eth_getBlockByNumber", &json!([U256::from(target), full]))?;
+
+        let history =
+            provider.request("eth_getProof", &json!([address, [slot], U256::from(block)]))?;
+
+        Ok((header, history))
+    }
+}
+
+impl DomainController for Ethereum {
+    const ID: &str = Self::ID;
+
+    fn state_proof(&self, args: Value) -> anyhow::Result<StateProof> {
+        let provider = EthereumProviderConfig::from_args(&args)?;
 
-        Ethereum::encode_proof(proof, args)
+        Self::state_proof_with(&provider, args)
     }
 }
@@ -0,0 +1,185 @@
+use alloc::{
+    string::{String, ToString as _},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{EthereumBlockRange, EthereumNetwork};
+
+/// Errors of the Ethereum domain.
+///
+/// Converts into `anyhow::Error`, and can be recovered from it with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EthereumError {
+    /// Malformed witness or RPC response.
+    Decode { what: &'static str, reason: String },
+
+    /// Invalid proof arguments or provider configuration.
+    Config { reason: String },
+
+    /// The JSON-RPC request failed.
+    Rpc { method: String, reason: String },
+
+    /// The proof is not from the domain of the network.
+    DomainMismatch { network: EthereumNetwork },
+
+    /// The proven block is not within the accepted range.
+    BlockOutOfRange {
+        number: u64,
+        range: EthereumBlockRange,
+    },
+
+    /// The block header isn't anchored to the validated state.
+    BlockHeader { number: u64, reason: String },
+
+    /// The inclusion proof of a receipt or transaction doesn't match the block.
+    InclusionProof { index: u64, reason: String },
+
+    /// The RPC returned the proof of another account.
+    AccountMismatch {
+        requested: Vec<u8>,
+        returned: Vec<u8>,
+    },
+
+    /// The RPC response misses a requested storage slot.
+    MissingSlot { key: Vec<u8> },
+
+    /// The slot isn't part of the proven storage.
+    UnprovenSlot { key: Vec<u8> },
+
+    /// The RPC response has a storage slot that wasn't requested.
+    UnexpectedSlot { key: Vec<u8> },
+
+    /// A provided storage value differs from the RPC response.
+    ValueMismatch { key: Vec<u8> },
+
+    /// The payload doesn't match the hash proven at its commitment slot.
+    PayloadMismatch { key: Vec<u8> },
+
+    /// The account proof doesn't match the state root.
+    AccountProof { account: Vec<u8>, reason: String },
+
+    /// The bytecode of an account doesn't match its code hash.
+    CodeProof { account: Vec<u8>, reason: String },
+
+    /// The storage proof of a slot doesn't match the storage root of its account.
+    StorageProof {
+        account: Vec<u8>,
+        key: Vec<u8>,
+        reason: String,
+    },
+}
+
+impl fmt::Display for EthereumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode { what, reason } => write!(f, "invalid {what}: {reason}"),
+            Self::Config { reason } => write!(f, "invalid configuration: {reason}"),
+            Self::Rpc { method, reason } => write!(f, "`{method}` failed: {reason}"),
+            Self::DomainMismatch { network } => {
+                write!(f, "the proof is not from the {network} domain")
+            }
+            Self::BlockOutOfRange { number, range } => {
+                write!(f, "block {number} is out of the accepted range ")?;
+
+                match range.min {
+                    Some(min) => write!(f, "{min}..=")?,
+                    None => f.write_str("..=")?,
+                }
+
+                match range.max {
+                    Some(max) => write!(f, "{max}"),
+                    None => f.write_str("latest"),
+                }
+            }
+            Self::BlockHeader { number, reason } => write!(f, "block {number}: {reason}"),
+            Self::InclusionProof { index, reason } => {
+                write!(f, "inclusion proof of index {index} failed: {reason}")
+            }
+            Self::AccountMismatch {
+                requested,
+                returned,
+            } => write!(
+                f,
+                "requested account {}, got {}",
+                Hex(requested),
+                Hex(returned)
+            ),
+            Self::MissingSlot { key } => {
+                write!(f, "slot {} is missing from the response", Hex(key))
+            }
+            Self::UnprovenSlot { key } => write!(f, "slot {} is not proven", Hex(key)),
+            Self::UnexpectedSlot { key } => {
+                write!(f, "slot {} was not requested", Hex(key))
+            }
+            Self::ValueMismatch { key } => {
+                write!(
+                    f,
+                    "the value of slot {} differs from the response",
+                    Hex(key)
+                )
+            }
+            Self::PayloadMismatch { key } => {
+                write!(
+                    f,
+                    "the payload doesn't match its commitment at slot {}",
+                    Hex(key)
+                )
+            }
+            Self::AccountProof { account, reason } => {
+                write!(f, "account proof of {} failed: {reason}", Hex(account))
+            }
+            Self::CodeProof { account, reason } => {
+                write!(f, "code proof of {} failed: {reason}", Hex(account))
+            }
+            Self::StorageProof {
+                account,
+                key,
+                reason,
+            } => write!(
+                f,
+                "storage proof of slot {} of {} failed: {reason}",
+                Hex(key),
+                Hex(account)
+            ),
+        }
+    }
+}
+
+impl core::error::Error for EthereumError {}
+
+impl EthereumError {
+    /// Creates a [Self::Decode] error.
+    pub fn decode(what: &'static str, reason: impl fmt::Display) -> Self {
+        Self::Decode {
+            what,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Creates a [Self::Config] error.
+    pub fn config(reason: impl fmt::Display) -> Self {
+        Self::Config {
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Creates a [Self::BlockHeader] error.
+    pub fn block_header(number: u64, reason: impl fmt::Display) -> Self {
+        Self::BlockHeader {
+            number,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Formats bytes as `0x` prefixed hex.
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("0x")?;
+
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
@@ -0,0 +1,122 @@
+use alloc::vec::Vec;
+use msgpacker::{MsgPacker, Packable as _, Unpackable as _};
+use serde::{Deserialize, Serialize};
+use valence_coprocessor::Hash;
+
+use crate::{
+    EthereumAccountProof, EthereumAccountProofArgs, EthereumNetwork, EthereumProvenAccount,
+    EthereumStateProof,
+};
+
+/// Proof of an ancestor block header.
+///
+/// The header hash is proven to be stored by the EIP-2935 history contract in the validated
+/// state, so the proven block must be within [crate::Ethereum::HISTORY_SERVE_WINDOW] blocks
+/// before the validated block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumHeaderProof {
+    /// RLP encoded block header.
+    pub header: Vec<u8>,
+
+    /// Proof of the history contract slot holding the header hash.
+    pub history: EthereumAccountProof,
+}
+
+/// Proof of an ancestor block header, with accounts proven against its state root.
+///
+/// Blocks older than the history window are reached from the oldest block of the window by
+/// following the parent hashes of the `ancestors` headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumBlockProof {
+    pub state_root: Hash,
+    /// Header of the block anchored by the history contract.
+    pub block: EthereumHeaderProof,
+    /// RLP encoded headers from the parent of the anchored block down to the proven block.
+    pub ancestors: Vec<Vec<u8>>,
+    /// Accounts proven against the state root of the proven block.
+    pub accounts: Vec<EthereumAccountProof>,
+}
+
+impl EthereumBlockProof {
+    /// Wire format version, following [EthereumStateProof::MAGIC].
+    pub const VERSION: u8 = 0x93;
+
+    /// Serializes the proof into its versioned wire format.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(512);
+
+        bytes.push(EthereumStateProof::MAGIC);
+        bytes.push(Self::VERSION);
+
+        self.pack(&mut bytes);
+
+        bytes
+    }
+
+    /// Deserializes a block proof.
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, msgpacker::Error> {
+        match bytes {
+            [EthereumStateProof::MAGIC, Self::VERSION, proof @ ..] => Ok(Self::unpack(proof)?.1),
+            _ => Err(msgpacker::Error::UnexpectedFormatTag),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumProvenBlock {
+    /// Block number.
+    pub number: u64,
+
+    /// Block hash.
+    pub hash: Hash,
+
+    /// Hash of the parent block.
+    pub parent_hash: Hash,
+
+    /// State trie root after the block execution.
+    pub state_root: Hash,
+
+    /// Receipts trie root of the block.
+    pub receipts_root: Hash,
+
+    /// Transactions trie root of the block.
+    pub transactions_root: Hash,
+
+    /// Block timestamp, in seconds.
+    pub timestamp: u64,
+
+    /// EIP-1559 base fee per gas.
+    pub base_fee_per_gas: Option<u64>,
+
+    /// Accounts proven against the state root of the block.
+    pub accounts: Vec<EthereumProvenAccount>,
+
+    /// User payload.
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthereumBlockProofArgs {
+    /// Validated block number.
+    pub block: u64,
+
+    /// State root [Hash] of the validated block.
+    pub root: Hash,
+
+    /// Number of the proven block.
+    ///
+    /// Blocks older than [crate::Ethereum::HISTORY_SERVE_WINDOW] require one header per block
+    /// past the window.
+    pub target: u64,
+
+    /// Accounts to be proven at the proven block.
+    #[serde(default)]
+    pub accounts: Vec<EthereumAccountProofArgs>,
+
+    /// Payload to be forwarded to the circuit.
+    pub payload: Vec<u8>,
+
+    /// Network of the proven block.
+    #[serde(default)]
+    pub network: EthereumNetwork,
+}
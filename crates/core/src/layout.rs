@@ -0,0 +1,679 @@
+use alloc::{string::String, vec::Vec};
+use alloy_primitives::{b256, hex, keccak256, Address, Signed, Uint, B256, I256, U256};
+
+use crate::{Ethereum, EthereumError, EthereumProvenAccount, EthereumStorageProofArg};
+
+/// Key of a Solidity mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EthereumMappingKey {
+    /// An `address` key.
+    Address(Address),
+
+    /// A `bytes32` key.
+    Bytes32(B256),
+
+    /// A `uintN` key.
+    Uint(U256),
+
+    /// An `intN` key.
+    Int(I256),
+
+    /// A `bool` key.
+    Bool(bool),
+
+    /// A `string` or `bytes` key.
+    Bytes(Vec<u8>),
+}
+
+impl EthereumMappingKey {
+    /// Encodes the key as it is hashed with the mapping slot.
+    ///
+    /// Value types are padded to 32 bytes, while `string` and `bytes` keys are used as-is.
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Address(address) => B256::left_padding_from(address.as_slice()).to_vec(),
+            Self::Bytes32(bytes) => bytes.to_vec(),
+            Self::Uint(value) => value.to_be_bytes::<32>().to_vec(),
+            Self::Int(value) => value.to_be_bytes::<32>().to_vec(),
+            Self::Bool(value) => U256::from(*value as u8).to_be_bytes::<32>().to_vec(),
+            Self::Bytes(bytes) => bytes.clone(),
+        }
+    }
+
+    /// Computes the slot of the key entry in the mapping stored at `slot`.
+    pub fn slot(&self, slot: U256) -> U256 {
+        let mut preimage = self.encode();
+
+        preimage.extend_from_slice(&slot.to_be_bytes::<32>());
+
+        U256::from_be_bytes(keccak256(preimage).0)
+    }
+}
+
+impl From<Address> for EthereumMappingKey {
+    fn from(address: Address) -> Self {
+        Self::Address(address)
+    }
+}
+
+impl From<B256> for EthereumMappingKey {
+    fn from(bytes: B256) -> Self {
+        Self::Bytes32(bytes)
+    }
+}
+
+impl From<[u8; 32]> for EthereumMappingKey {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self::Bytes32(bytes.into())
+    }
+}
+
+impl From<U256> for EthereumMappingKey {
+    fn from(value: U256) -> Self {
+        Self::Uint(value)
+    }
+}
+
+impl From<I256> for EthereumMappingKey {
+    fn from(value: I256) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<bool> for EthereumMappingKey {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<String> for EthereumMappingKey {
+    fn from(value: String) -> Self {
+        Self::Bytes(value.into_bytes())
+    }
+}
+
+impl From<&str> for EthereumMappingKey {
+    fn from(value: &str) -> Self {
+        Self::Bytes(value.as_bytes().to_vec())
+    }
+}
+
+impl From<Vec<u8>> for EthereumMappingKey {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for EthereumMappingKey {
+    fn from(value: &[u8]) -> Self {
+        Self::Bytes(value.to_vec())
+    }
+}
+
+macro_rules! impl_uint_key {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for EthereumMappingKey {
+                fn from(value: $t) -> Self {
+                    Self::Uint(U256::from(value))
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_int_key {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for EthereumMappingKey {
+                fn from(value: $t) -> Self {
+                    Self::Int(I256::unchecked_from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_uint_key!(u8, u16, u32, u64, u128);
+impl_int_key!(i8, i16, i32, i64, i128);
+
+/// Standard storage slot of a proxy contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EthereumProxySlot {
+    /// EIP-1967 logic contract address.
+    Implementation,
+
+    /// EIP-1967 address allowed to upgrade the proxy.
+    Admin,
+
+    /// EIP-1967 beacon contract address, holding the implementation of beacon proxies.
+    Beacon,
+
+    /// EIP-1822 (UUPS) logic contract address.
+    Proxiable,
+}
+
+impl EthereumProxySlot {
+    /// All the proxy slots.
+    pub const ALL: [Self; 4] = [
+        Self::Implementation,
+        Self::Admin,
+        Self::Beacon,
+        Self::Proxiable,
+    ];
+
+    /// Slot of the address, as `keccak256("eip1967.proxy.<name>") - 1` for EIP-1967 and
+    /// `keccak256("PROXIABLE")` for EIP-1822.
+    pub const fn key(&self) -> B256 {
+        match self {
+            Self::Implementation => {
+                b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc")
+            }
+            Self::Admin => {
+                b256!("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103")
+            }
+            Self::Beacon => {
+                b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50")
+            }
+            Self::Proxiable => {
+                b256!("c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7")
+            }
+        }
+    }
+
+    /// Slot of the address.
+    pub fn slot(&self) -> U256 {
+        U256::from_be_bytes(self.key().0)
+    }
+}
+
+/// Position of a value packed into a storage slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthereumSlotPosition {
+    slot: U256,
+    offset: usize,
+    size: usize,
+}
+
+impl EthereumSlotPosition {
+    /// Position of the value of `size` bytes at `offset` of `slot`.
+    ///
+    /// Fails if the value doesn't fit in the slot.
+    pub fn new(slot: U256, offset: usize, size: usize) -> Result<Self, EthereumError> {
+        if offset.checked_add(size).is_none_or(|end| end > 32) {
+            return Err(EthereumError::config(format_args!(
+                "a value of {size} bytes at offset {offset} doesn't fit in a slot"
+            )));
+        }
+
+        Ok(Self { slot, offset, size })
+    }
+
+    /// Position of a value the layout already fits in the slot.
+    pub(crate) const fn fitting(slot: U256, offset: usize, size: usize) -> Self {
+        Self { slot, offset, size }
+    }
+
+    /// Storage slot holding the value.
+    pub fn slot(&self) -> U256 {
+        self.slot
+    }
+
+    /// Offset of the value in bytes, starting from the lowest-order byte of the slot.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Size of the value in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Reads the value from the big-endian slot word.
+    pub fn read<'a>(&self, word: &'a [u8; 32]) -> &'a [u8] {
+        let end = 32 - self.offset;
+
+        &word[end - self.size..end]
+    }
+
+    /// Writes the big-endian value into the slot word, truncating it to its size.
+    pub fn write(&self, word: &mut [u8; 32], value: &[u8]) {
+        let end = 32 - self.offset;
+        let len = value.len().min(self.size);
+
+        word[end - self.size..end].fill(0);
+        word[end - len..end].copy_from_slice(&value[value.len() - len..]);
+    }
+}
+
+/// Layout of the elements of a Solidity array.
+///
+/// Elements smaller than a slot are packed, as many as fit, into each slot; larger elements, such
+/// as structs, start a new slot each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthereumArrayLayout {
+    /// Slot of the first element.
+    pub data: U256,
+
+    /// Size of an element in bytes.
+    pub element_size: usize,
+}
+
+impl EthereumArrayLayout {
+    /// Layout of the `T[]` array stored at `slot`, where its length is kept.
+    pub fn dynamic(slot: U256, element_size: usize) -> Self {
+        Self {
+            data: U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0),
+            element_size,
+        }
+    }
+
+    /// Layout of the `T[N]` array starting at `slot`.
+    pub fn fixed(slot: U256, element_size: usize) -> Self {
+        Self {
+            data: slot,
+            element_size,
+        }
+    }
+
+    /// Position of the element at `index`.
+    pub fn position(&self, index: u64) -> EthereumSlotPosition {
+        let size = self.element_size.clamp(1, 32);
+
+        if self.element_size >= 32 {
+            let slots = self.element_size.div_ceil(32) as u64;
+
+            return EthereumSlotPosition::fitting(
+                self.data + U256::from(index) * U256::from(slots),
+                0,
+                size,
+            );
+        }
+
+        let per_slot = (32 / size) as u64;
+
+        EthereumSlotPosition::fitting(
+            self.data + U256::from(index / per_slot),
+            (index % per_slot) as usize * size,
+            size,
+        )
+    }
+
+    /// Number of slots used by `len` elements.
+    pub fn slots(&self, len: u64) -> u64 {
+        if self.element_size >= 32 {
+            return len * self.element_size.div_ceil(32) as u64;
+        }
+
+        let per_slot = (32 / self.element_size.max(1)) as u64;
+
+        len.div_ceil(per_slot)
+    }
+}
+
+impl Ethereum {
+    /// Computes the ERC-7201 base slot of the namespace `id`, as
+    /// `keccak256(keccak256(id) - 1) & ~0xff`.
+    pub fn erc7201_slot(id: &str) -> U256 {
+        let id = U256::from_be_bytes(keccak256(id).0) - U256::ONE;
+        let slot = U256::from_be_bytes(keccak256(id.to_be_bytes::<32>()).0);
+
+        slot & !U256::from(0xff)
+    }
+
+    /// Encodes a slot word as its proven storage value.
+    ///
+    /// Zero slots are absent from the storage trie, so they are proven as `None`.
+    pub fn encode_slot_value(word: &[u8; 32]) -> Option<Vec<u8>> {
+        let value = U256::from_be_bytes(*word);
+
+        (!value.is_zero()).then(|| alloy_rlp::encode(value))
+    }
+
+    /// Decodes a proven storage value into its slot word.
+    pub fn decode_slot_value(value: Option<&[u8]>) -> anyhow::Result<[u8; 32]> {
+        let Some(value) = value else {
+            return Ok([0; 32]);
+        };
+
+        let value: U256 = alloy_rlp::decode_exact(value)
+            .map_err(|e| EthereumError::decode("storage value", e))?;
+
+        Ok(value.to_be_bytes())
+    }
+
+    /// Reads the word of `slot` from the proven storage values.
+    pub fn read_slot(storage: &[EthereumStorageProofArg], slot: U256) -> anyhow::Result<[u8; 32]> {
+        let key = slot.to_be_bytes::<32>();
+
+        storage
+            .iter()
+            .find(|s| s.key == key)
+            .ok_or_else(|| EthereumError::UnprovenSlot { key: key.to_vec() })?
+            .word()
+    }
+
+    /// Reads the `string` or `bytes` value stored at `slot` from the proven storage values.
+    ///
+    /// Values of 32 bytes or more are read from the data slots at the hash of `slot`.
+    pub fn read_bytes(storage: &[EthereumStorageProofArg], slot: U256) -> anyhow::Result<Vec<u8>> {
+        let word = Self::read_slot(storage, slot)?;
+
+        if word[31] & 1 == 0 {
+            let len = word[31] as usize / 2;
+
+            if len >= 32 {
+                return Err(EthereumError::decode(
+                    "bytes value",
+                    format_args!("short length {len}"),
+                )
+                .into());
+            }
+
+            return Ok(word[..len].to_vec());
+        }
+
+        let len = U256::from_be_bytes(word) >> 1usize;
+        let len: usize = len
+            .try_into()
+            .map_err(|_| EthereumError::decode("bytes value", format_args!("length {len}")))?;
+
+        // the length is controlled by the contract, so it is bounded by the proven slots before
+        // allocating
+        let slots = len.div_ceil(32);
+
+        if slots > storage.len() {
+            return Err(EthereumError::decode(
+                "bytes value",
+                format_args!("length {len} exceeds the {} proven slots", storage.len()),
+            )
+            .into());
+        }
+
+        let data = U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0);
+        let mut bytes = Vec::with_capacity(slots * 32);
+
+        for i in 0..slots {
+            bytes.extend(Self::read_slot(storage, data.wrapping_add(U256::from(i)))?);
+        }
+
+        bytes.truncate(len);
+
+        Ok(bytes)
+    }
+
+    /// Reads the `string` value stored at `slot` from the proven storage values.
+    pub fn read_string(storage: &[EthereumStorageProofArg], slot: U256) -> anyhow::Result<String> {
+        String::from_utf8(Self::read_bytes(storage, slot)?)
+            .map_err(|e| EthereumError::decode("string value", e).into())
+    }
+}
+
+impl EthereumStorageProofArg {
+    /// Returns the slot of the value.
+    pub fn slot(&self) -> anyhow::Result<U256> {
+        U256::try_from_be_slice(&self.key)
+            .filter(|_| self.key.len() == 32)
+            .ok_or_else(|| {
+                EthereumError::decode("storage key", format_args!("0x{}", hex::encode(&self.key)))
+                    .into()
+            })
+    }
+
+    /// Returns the slot word of the value.
+    pub fn word(&self) -> anyhow::Result<[u8; 32]> {
+        Ethereum::decode_slot_value(self.value.as_deref())
+    }
+
+    /// Decodes a `uint256` value.
+    pub fn to_u256(&self) -> anyhow::Result<U256> {
+        self.word().map(U256::from_be_bytes)
+    }
+
+    /// Decodes a `uintN` value, failing if it doesn't fit `BITS`.
+    pub fn to_uint<const BITS: usize, const LIMBS: usize>(
+        &self,
+    ) -> anyhow::Result<Uint<BITS, LIMBS>> {
+        let value = self.to_u256()?;
+
+        Uint::checked_from_limbs_slice(value.as_limbs())
+            .ok_or_else(|| self.mismatch(format_args!("uint{BITS}")))
+    }
+
+    /// Decodes an `intN` value stored in its `BITS / 8` bytes, as Solidity does.
+    pub fn to_int<const BITS: usize, const LIMBS: usize>(
+        &self,
+    ) -> anyhow::Result<Signed<BITS, LIMBS>> {
+        self.to_uint::<BITS, LIMBS>()
+            .map(Signed::from_raw)
+            .map_err(|_| self.mismatch(format_args!("int{BITS}")))
+    }
+
+    /// Decodes an `address` value, failing if the upper 12 bytes are set.
+    pub fn to_address(&self) -> anyhow::Result<Address> {
+        let word = self.word()?;
+
+        let (padding, address) = word.split_at(12);
+
+        anyhow::ensure!(padding.iter().all(|b| *b == 0), self.mismatch("address"));
+
+        Ok(Address::from_slice(address))
+    }
+
+    /// Decodes a `bool` value, failing if it is neither `0` nor `1`.
+    pub fn to_bool(&self) -> anyhow::Result<bool> {
+        match self.to_u256()? {
+            v if v.is_zero() => Ok(false),
+            v if v == U256::ONE => Ok(true),
+            _ => Err(self.mismatch("bool")),
+        }
+    }
+
+    /// Decodes a `bytes32` value.
+    pub fn to_bytes32(&self) -> anyhow::Result<B256> {
+        self.word().map(B256::from)
+    }
+
+    fn mismatch<T: core::fmt::Display>(&self, ty: T) -> anyhow::Error {
+        EthereumError::decode(
+            "storage value",
+            format_args!("slot 0x{} is not a valid {ty}", hex::encode(&self.key)),
+        )
+        .into()
+    }
+}
+
+impl EthereumProvenAccount {
+    /// Returns the proven value of `slot`.
+    pub fn storage_value(&self, slot: U256) -> anyhow::Result<&EthereumStorageProofArg> {
+        let key = slot.to_be_bytes::<32>();
+
+        self.storage
+            .iter()
+            .find(|s| s.key == key)
+            .ok_or_else(|| EthereumError::UnprovenSlot { key: key.to_vec() }.into())
+    }
+
+    /// Returns the proven address of a proxy `slot`.
+    ///
+    /// The address is zero if the slot is empty, such as the admin of a UUPS proxy.
+    pub fn proxy_address(&self, slot: EthereumProxySlot) -> anyhow::Result<Address> {
+        self.storage_value(slot.slot())?.to_address()
+    }
+
+    /// Returns the proven EIP-1967 implementation address, falling back to the EIP-1822 slot if it
+    /// is empty or not proven.
+    pub fn implementation(&self) -> anyhow::Result<Address> {
+        let implementation = self.proxy_address(EthereumProxySlot::Implementation);
+
+        match implementation {
+            Ok(address) if !address.is_zero() => Ok(address),
+            _ => self
+                .proxy_address(EthereumProxySlot::Proxiable)
+                .or_else(|e| implementation.map_err(|_| e)),
+        }
+    }
+
+    /// Returns the proven EIP-1967 admin address.
+    pub fn admin(&self) -> anyhow::Result<Address> {
+        self.proxy_address(EthereumProxySlot::Admin)
+    }
+
+    /// Ensures the payload is committed by the account, with its keccak hash proven at `slot`.
+    ///
+    /// The payload isn't part of the proven state, so a relayer could replace it; the commitment
+    /// binds it to the state root. The circuit must set the slot itself, and check the account.
+    pub fn verify_payload(&self, slot: U256) -> anyhow::Result<()> {
+        let commitment = self.storage_value(slot)?;
+
+        if commitment.to_bytes32()? != keccak256(&self.payload) {
+            return Err(EthereumError::PayloadMismatch {
+                key: commitment.key.clone(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Reads the `string` value stored at `slot`, as laid out by `add_string_value`.
+    pub fn read_string(&self, slot: U256) -> anyhow::Result<String> {
+        Ethereum::read_string(&self.storage, slot)
+    }
+
+    /// Reads the `bytes` value stored at `slot`.
+    pub fn read_bytes(&self, slot: U256) -> anyhow::Result<Vec<u8>> {
+        Ethereum::read_bytes(&self.storage, slot)
+    }
+}
+
+/// Slot words of a storage layout, kept in the order they are first written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EthereumStorageWords {
+    words: Vec<(U256, [u8; 32])>,
+}
+
+impl EthereumStorageWords {
+    /// Returns the word of `slot`, starting from zero if it wasn't written.
+    pub fn word_mut(&mut self, slot: U256) -> &mut [u8; 32] {
+        let i = match self.words.iter().position(|(s, _)| *s == slot) {
+            Some(i) => i,
+            None => {
+                self.words.push((slot, [0; 32]));
+                self.words.len() - 1
+            }
+        };
+
+        &mut self.words[i].1
+    }
+
+    /// Writes the big-endian value at its position, preserving the other values of the slot.
+    pub fn write(&mut self, position: EthereumSlotPosition, value: &[u8]) {
+        position.write(self.word_mut(position.slot()), value);
+    }
+
+    /// Writes a `string` or `bytes` value at `slot`.
+    ///
+    /// Values shorter than 32 bytes are stored in the slot with their length; longer values are
+    /// stored at the hash of `slot`.
+    pub fn write_bytes(&mut self, slot: U256, value: &[u8]) {
+        if value.len() < 32 {
+            let word = self.word_mut(slot);
+
+            *word = [0; 32];
+            word[..value.len()].copy_from_slice(value);
+            word[31] = (value.len() * 2) as u8;
+
+            return;
+        }
+
+        *self.word_mut(slot) = U256::from(value.len() * 2 + 1).to_be_bytes();
+
+        let data = U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0);
+
+        for (i, chunk) in value.chunks(32).enumerate() {
+            let word = self.word_mut(data + U256::from(i));
+
+            *word = [0; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+        }
+    }
+
+    /// Returns the storage proof arguments of the words.
+    pub fn build(self) -> Vec<EthereumStorageProofArg> {
+        self.words
+            .into_iter()
+            .map(|(slot, word)| EthereumStorageProofArg {
+                key: slot.to_be_bytes::<32>().to_vec(),
+                value: Ethereum::encode_slot_value(&word),
+            })
+            .collect()
+    }
+}
+
+/// Layout of contiguous fields packed into storage slots, as Solidity lays out struct members and
+/// state variables.
+///
+/// Fields are declared in order with their size in bytes, and packed from the lowest-order bytes
+/// of a slot. A field that doesn't fit the remainder of a slot starts the next one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EthereumPackedLayout {
+    sizes: Vec<usize>,
+}
+
+impl EthereumPackedLayout {
+    pub fn new<I>(sizes: I) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        Self {
+            sizes: sizes.into_iter().map(|s| s.clamp(1, 32)).collect(),
+        }
+    }
+
+    /// Positions of the fields, starting at `slot`.
+    pub fn positions(&self, slot: U256) -> Vec<EthereumSlotPosition> {
+        let mut slot = slot;
+        let mut offset = 0;
+
+        self.sizes
+            .iter()
+            .map(|size| {
+                if offset + size > 32 {
+                    slot += U256::ONE;
+                    offset = 0;
+                }
+
+                let position = EthereumSlotPosition::fitting(slot, offset, *size);
+
+                offset += size;
+
+                position
+            })
+            .collect()
+    }
+
+    /// Number of slots used by the fields.
+    pub fn slots(&self) -> u64 {
+        self.positions(U256::ZERO)
+            .last()
+            .map(|p| p.slot().to::<u64>() + 1)
+            .unwrap_or(0)
+    }
+
+    /// Extracts the fields starting at `slot` from the proven storage values.
+    pub fn extract(
+        &self,
+        slot: U256,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Vec<Vec<u8>>> {
+        self.positions(slot)
+            .iter()
+            .map(|position| {
+                let word = Ethereum::read_slot(storage, position.slot())?;
+
+                Ok(position.read(&word).to_vec())
+            })
+            .collect()
+    }
+}
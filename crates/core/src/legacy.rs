@@ -0,0 +1,42 @@
+use alloc::{vec, vec::Vec};
+use msgpacker::MsgPacker;
+use serde::{Deserialize, Serialize};
+use valence_coprocessor::Hash;
+
+use crate::{EthereumAccountProof, EthereumStateProof, EthereumStorageProof};
+
+/// Legacy unversioned [EthereumStateProof], with the balance truncated to `u64`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumStateProofV0 {
+    pub state_root: Hash,
+    pub account: Vec<u8>,
+    pub nonce: u64,
+    pub balance: u64,
+    pub storage_root: Hash,
+    pub code_hash: Hash,
+    pub account_proof: Vec<Vec<u8>>,
+    pub storage_proofs: Vec<EthereumStorageProof>,
+}
+
+impl From<EthereumStateProofV0> for EthereumStateProof {
+    fn from(proof: EthereumStateProofV0) -> Self {
+        let mut balance = [0u8; 32];
+
+        balance[24..].copy_from_slice(&proof.balance.to_be_bytes());
+
+        Self {
+            state_root: proof.state_root,
+            accounts: vec![EthereumAccountProof {
+                account: proof.account,
+                exists: true,
+                nonce: proof.nonce,
+                balance,
+                storage_root: proof.storage_root,
+                code_hash: proof.code_hash,
+                account_proof: proof.account_proof,
+                storage_proofs: proof.storage_proofs,
+            }],
+            codes: Vec::new(),
+        }
+    }
+}
@@ -1,36 +1,239 @@
 #![no_std]
 
-use alloc::{string::String, vec::Vec};
-use msgpacker::MsgPacker;
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt, str::FromStr};
+use msgpacker::{MsgPacker, Packable as _, Unpackable as _};
 use serde::{Deserialize, Serialize};
-use valence_coprocessor::Hash;
+use valence_coprocessor::{DomainData, Hash};
 
 extern crate alloc;
 
 pub struct Ethereum;
 
 impl Ethereum {
-    pub const ID: &str = "ethereum-electra-alpha";
-    pub const NETWORK: &str = "eth-mainnet";
+    /// Domain identifier of mainnet; see [EthereumNetwork::id] for the other networks.
+    pub const ID: &str = EthereumNetwork::Mainnet.id();
+    pub const NETWORK: &str = EthereumNetwork::Mainnet.alchemy();
+
+    /// EIP-2935 contract serving the hashes of the recent blocks.
+    pub const HISTORY_STORAGE_ADDRESS: [u8; 20] = [
+        0x00, 0x00, 0xf9, 0x08, 0x27, 0xf1, 0xc5, 0x3a, 0x10, 0xcb, 0x7a, 0x02, 0x33, 0x5b, 0x17,
+        0x53, 0x20, 0x00, 0x29, 0x35,
+    ];
+
+    /// Number of block hashes kept by [Self::HISTORY_STORAGE_ADDRESS].
+    pub const HISTORY_SERVE_WINDOW: u64 = 8191;
+}
+
+/// Ethereum network of a domain.
+///
+/// Only mainnet has a light client; a testnet is added with its own light client and domain.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EthereumNetwork {
+    #[default]
+    Mainnet,
+}
+
+impl EthereumNetwork {
+    /// All the supported networks.
+    pub const ALL: [Self; 1] = [Self::Mainnet];
+
+    /// Name of the network.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "mainnet",
+        }
+    }
+
+    /// Co-processor domain identifier of the network.
+    pub const fn id(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "ethereum-electra-alpha",
+        }
+    }
+
+    /// Alchemy network of the execution layer requests.
+    pub const fn alchemy(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "eth-mainnet",
+        }
+    }
+
+    /// EIP-155 chain id.
+    pub const fn chain_id(&self) -> u64 {
+        match self {
+            Self::Mainnet => 1,
+        }
+    }
+
+    /// Co-processor domain of the network.
+    pub fn domain(&self) -> Hash {
+        DomainData::identifier_from_parts(self.id())
+    }
+}
+
+impl fmt::Display for EthereumNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for EthereumNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|n| n.name() == s)
+            .ok_or_else(|| format!("unknown network `{s}`"))
+    }
+}
+
+/// Inclusive range of block numbers a proof is accepted from.
+///
+/// Unbounded by default.
+///
+/// The circuits don't know the latest validated block, so a maximum age can't be enforced
+/// against it; circuits requiring recent proofs set the lowest accepted block with
+/// [Self::with_min] instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EthereumBlockRange {
+    /// Lowest accepted block number.
+    pub min: Option<u64>,
+
+    /// Highest accepted block number.
+    pub max: Option<u64>,
+}
+
+impl EthereumBlockRange {
+    /// Accepts only the block `number`.
+    pub const fn exact(number: u64) -> Self {
+        Self {
+            min: Some(number),
+            max: Some(number),
+        }
+    }
+
+    /// Rejects the blocks before `number`.
+    pub fn with_min(mut self, number: u64) -> Self {
+        self.min = Some(self.min.map_or(number, |min| min.max(number)));
+        self
+    }
+
+    /// Rejects the blocks after `number`.
+    pub fn with_max(mut self, number: u64) -> Self {
+        self.max = Some(self.max.map_or(number, |max| max.min(number)));
+        self
+    }
+
+    /// Returns `true` if the block `number` is within the range.
+    pub fn contains(&self, number: u64) -> bool {
+        self.min.is_none_or(|min| min <= number) && self.max.is_none_or(|max| number <= max)
+    }
+
+    /// Fails with [EthereumError::BlockOutOfRange] if the block `number` is not within the range.
+    pub fn check(&self, number: u64) -> Result<(), EthereumError> {
+        if !self.contains(number) {
+            return Err(EthereumError::BlockOutOfRange {
+                number,
+                range: *self,
+            });
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, MsgPacker)]
 pub struct EthereumStateProof {
     pub state_root: Hash,
+    /// Accounts proven against the state root.
+    pub accounts: Vec<EthereumAccountProof>,
+    /// Bytecode of the accounts that requested it.
+    pub codes: Vec<EthereumCodeProof>,
+}
+
+/// Bytecode of an account, proven by its keccak against the account code hash.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumCodeProof {
+    pub account: Vec<u8>,
+    pub code: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumAccountProof {
     pub account: Vec<u8>,
+    /// `false` if the proof attests the account is absent from the state trie.
+    pub exists: bool,
     pub nonce: u64,
-    pub balance: u64,
+    /// Big-endian encoded account balance.
+    pub balance: [u8; 32],
     pub storage_root: Hash,
     pub code_hash: Hash,
     pub account_proof: Vec<Vec<u8>>,
     pub storage_proofs: Vec<EthereumStorageProof>,
 }
 
+impl EthereumStateProof {
+    /// Prefix of versioned proofs.
+    ///
+    /// `0xc1` is never used by msgpack, so it cannot be the first byte of a legacy proof.
+    ///
+    /// The byte after the prefix selects the proof type and its version: state proofs count up
+    /// from `0x01`, compact state proofs from `0x81`, and the receipt, transaction and block
+    /// proofs use `0x91`, `0x92` and `0x93`.
+    pub const MAGIC: u8 = 0xc1;
+
+    /// Current wire format version.
+    pub const VERSION: u8 = 1;
+
+    /// Serializes the proof into its versioned wire format.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(512);
+
+        bytes.push(Self::MAGIC);
+        bytes.push(Self::VERSION);
+
+        self.pack(&mut bytes);
+
+        bytes
+    }
+
+    /// Deserializes a proof from any of its supported wire formats.
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, msgpacker::Error> {
+        match bytes {
+            [Self::MAGIC, Self::VERSION, proof @ ..] => Ok(Self::unpack(proof)?.1),
+            _ => Ok(EthereumStateProofV0::unpack(bytes)?.1.into()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, MsgPacker)]
 pub struct EthereumProvenAccount {
     /// Account address.
     pub account: Vec<u8>,
 
+    /// `false` if the account is proven to be absent from the state trie.
+    pub exists: bool,
+
+    /// Proven account nonce.
+    pub nonce: u64,
+
+    /// Proven big-endian encoded account balance.
+    pub balance: [u8; 32],
+
+    /// Proven storage trie root of the account.
+    pub storage_root: Hash,
+
+    /// Proven hash of the account code.
+    pub code_hash: Hash,
+
+    /// Proven bytecode of the account, if requested.
+    pub code: Option<Vec<u8>>,
+
     /// RLP encoded proven storage values.
     pub storage: Vec<EthereumStorageProofArg>,
 
@@ -38,6 +241,13 @@ pub struct EthereumProvenAccount {
     pub payload: Vec<u8>,
 }
 
+/// Accounts proven against the same state root.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumProvenAccounts {
+    /// Proven accounts, in the order they were requested.
+    pub accounts: Vec<EthereumProvenAccount>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
 pub struct EthereumStorageProofArg {
     /// The computed storage key for the storage slot.
@@ -59,6 +269,53 @@ pub struct EthereumStorageProof {
     pub proof: Vec<Vec<u8>>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EthereumProofMode {
+    /// Proves the account and the requested storage slots.
+    #[default]
+    Storage,
+
+    /// Proves only the account fields, skipping the storage section.
+    Account,
+
+    /// Proves the address has no account at the state root.
+    NonExistence,
+}
+
+/// Source of the proven storage values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EthereumStorageValues {
+    /// Proves the values of the storage arguments.
+    #[default]
+    Provided,
+
+    /// Proves the values of the `eth_getProof` response, so only the slot keys are required.
+    Fetched,
+
+    /// Proves the values of the storage arguments, failing if they differ from the `eth_getProof`
+    /// response.
+    Checked,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthereumAccountProofArgs {
+    /// Contract address.
+    pub address: String,
+
+    /// List of storage entries to be proven.
+    pub storage: Vec<EthereumStorageProofArg>,
+
+    /// What should be proven about the account.
+    #[serde(default)]
+    pub mode: EthereumProofMode,
+
+    /// Proves the bytecode of the account.
+    #[serde(default)]
+    pub code: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EthereumStateProofArgs {
     /// Contract address.
@@ -75,6 +332,91 @@ pub struct EthereumStateProofArgs {
 
     /// Payload to be forwarded to the circuit.
     pub payload: Vec<u8>,
+
+    /// What should be proven about the account.
+    #[serde(default)]
+    pub mode: EthereumProofMode,
+
+    /// Additional accounts to be proven at the same block.
+    #[serde(default)]
+    pub accounts: Vec<EthereumAccountProofArgs>,
+
+    /// Encodes the proof as [EthereumCompactStateProof].
+    #[serde(default)]
+    pub compact: bool,
+
+    /// Source of the proven storage values of all the accounts.
+    #[serde(default)]
+    pub values: EthereumStorageValues,
+
+    /// Network of the proven account.
+    #[serde(default)]
+    pub network: EthereumNetwork,
+
+    /// Proves the bytecode of the account.
+    #[serde(default)]
+    pub code: bool,
+
+    /// Storage slot keys of the account whose values are taken from the `eth_getProof`
+    /// response, whatever the `values` source.
+    #[serde(default)]
+    pub fetched: Vec<Vec<u8>>,
+}
+
+/// Arguments of a state proof request, tagged by `kind`.
+///
+/// Untagged arguments are read as [EthereumStateProofArgs].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EthereumProofArgs {
+    State(EthereumStateProofArgs),
+    Receipt(EthereumReceiptProofArgs),
+    Transaction(EthereumTransactionProofArgs),
+    Block(EthereumBlockProofArgs),
+}
+
+mod compact;
+mod error;
+mod header;
+mod legacy;
+mod receipt;
+mod transaction;
+
+pub use compact::*;
+pub use error::*;
+pub use header::*;
+pub use legacy::*;
+pub use receipt::*;
+pub use transaction::*;
+
+#[cfg(any(feature = "circuit", feature = "controller"))]
+mod layout;
+
+#[cfg(any(feature = "circuit", feature = "controller"))]
+mod solc;
+
+#[cfg(any(feature = "circuit", feature = "controller"))]
+mod storage;
+
+#[cfg(any(feature = "circuit", feature = "controller"))]
+mod verify;
+
+#[cfg(any(feature = "circuit", feature = "controller"))]
+pub use layout::*;
+
+#[cfg(any(feature = "circuit", feature = "controller"))]
+pub use solc::*;
+
+#[cfg(any(feature = "circuit", feature = "controller"))]
+pub use storage::*;
+
+#[cfg(feature = "derive")]
+pub use valence_coprocessor_ethereum_derive::StorageLayout;
+
+#[doc(hidden)]
+#[cfg(any(feature = "circuit", feature = "controller"))]
+pub mod __private {
+    pub use anyhow;
 }
 
 #[cfg(feature = "circuit")]
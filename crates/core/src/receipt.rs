@@ -0,0 +1,111 @@
+use alloc::vec::Vec;
+use msgpacker::{MsgPacker, Packable as _, Unpackable as _};
+use serde::{Deserialize, Serialize};
+use valence_coprocessor::Hash;
+
+use crate::{EthereumHeaderProof, EthereumNetwork, EthereumStateProof};
+
+/// Inclusion proofs of the receipts of an ancestor block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumReceiptProof {
+    pub state_root: Hash,
+    /// Header of the block containing the receipts.
+    pub block: EthereumHeaderProof,
+    /// Receipts proven against the receipts root of the block.
+    pub receipts: Vec<EthereumReceiptInclusionProof>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumReceiptInclusionProof {
+    /// Index of the transaction in the block.
+    pub index: u64,
+
+    /// EIP-2718 encoded receipt.
+    pub receipt: Vec<u8>,
+
+    /// The Merkle receipt proof.
+    pub proof: Vec<Vec<u8>>,
+}
+
+impl EthereumReceiptProof {
+    /// Wire format version, following [EthereumStateProof::MAGIC].
+    pub const VERSION: u8 = 0x91;
+
+    /// Serializes the proof into its versioned wire format.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(512);
+
+        bytes.push(EthereumStateProof::MAGIC);
+        bytes.push(Self::VERSION);
+
+        self.pack(&mut bytes);
+
+        bytes
+    }
+
+    /// Deserializes a receipt proof.
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, msgpacker::Error> {
+        match bytes {
+            [EthereumStateProof::MAGIC, Self::VERSION, proof @ ..] => Ok(Self::unpack(proof)?.1),
+            _ => Err(msgpacker::Error::UnexpectedFormatTag),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumProvenReceipt {
+    /// Number of the block containing the transaction.
+    pub block_number: u64,
+
+    /// Hash of the block containing the transaction.
+    pub block_hash: Hash,
+
+    /// Index of the transaction in the block.
+    pub transaction_index: u64,
+
+    /// EIP-2718 transaction type.
+    pub tx_type: u8,
+
+    /// `true` if the transaction succeeded.
+    pub status: bool,
+
+    /// Logs emitted by the transaction.
+    pub logs: Vec<EthereumLog>,
+
+    /// User payload.
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumLog {
+    /// Address of the emitting contract.
+    pub address: Vec<u8>,
+
+    /// Indexed topics of the event.
+    pub topics: Vec<Hash>,
+
+    /// ABI encoded event data.
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthereumReceiptProofArgs {
+    /// Validated block number.
+    pub block: u64,
+
+    /// State root [Hash] of the validated block.
+    pub root: Hash,
+
+    /// Number of the block containing the transactions.
+    pub target: u64,
+
+    /// Indexes of the transactions in the target block.
+    pub transactions: Vec<u64>,
+
+    /// Payload to be forwarded to the circuit.
+    pub payload: Vec<u8>,
+
+    /// Network of the proven block.
+    #[serde(default)]
+    pub network: EthereumNetwork,
+}
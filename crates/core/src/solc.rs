@@ -0,0 +1,489 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString as _},
+    vec::Vec,
+};
+use alloy_primitives::{hex, Address, B256, I256, U256};
+use core::{fmt::Display, str::FromStr};
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    Ethereum, EthereumArrayLayout, EthereumError, EthereumMappingKey, EthereumSlotPosition,
+    EthereumStorageProofArg, EthereumStorageWords,
+};
+
+/// Storage layout of a contract, as emitted by `solc --storage-layout` or
+/// `forge inspect <contract> storageLayout --json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct EthereumSolcStorageLayout {
+    /// State variables of the contract.
+    pub storage: Vec<EthereumSolcStorageEntry>,
+
+    /// Types of the variables, indexed by their identifier.
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub types: BTreeMap<String, EthereumSolcType>,
+}
+
+/// A state variable or struct member.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EthereumSolcStorageEntry {
+    pub label: String,
+
+    /// Slot of the variable, relative to its struct for members.
+    #[serde(deserialize_with = "parse")]
+    pub slot: U256,
+
+    /// Offset of the variable in bytes, starting from the lowest-order byte of the slot.
+    pub offset: usize,
+
+    /// Identifier of the variable type.
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// A type of the storage layout.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EthereumSolcType {
+    pub encoding: EthereumSolcEncoding,
+
+    /// Solidity name of the type, such as `uint64` or `struct Vault.WithdrawRequest`.
+    pub label: String,
+
+    /// Size of the type in bytes.
+    #[serde(rename = "numberOfBytes", deserialize_with = "parse")]
+    pub number_of_bytes: usize,
+
+    /// Key type of a mapping.
+    #[serde(default)]
+    pub key: Option<String>,
+
+    /// Value type of a mapping.
+    #[serde(default)]
+    pub value: Option<String>,
+
+    /// Element type of an array.
+    #[serde(default)]
+    pub base: Option<String>,
+
+    /// Members of a struct.
+    #[serde(default)]
+    pub members: Option<Vec<EthereumSolcStorageEntry>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EthereumSolcEncoding {
+    /// Stored in place, packed with the contiguous values.
+    Inplace,
+
+    /// A mapping, with entries at the hash of the key and the slot.
+    Mapping,
+
+    /// A `T[]` array, with its length at the slot and elements at its hash.
+    DynamicArray,
+
+    /// A `string` or `bytes` value.
+    Bytes,
+}
+
+/// A variable of the storage layout, resolved from its path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthereumSolcVariable<'a> {
+    /// Position of the variable in storage.
+    pub position: EthereumSlotPosition,
+
+    /// Type of the variable.
+    pub ty: &'a EthereumSolcType,
+}
+
+/// A value decoded from the proven storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EthereumSolcValue {
+    /// A `uintN` or `enum` value, or the length of a `T[]` array.
+    Uint(U256),
+
+    /// An `intN` value.
+    Int(I256),
+
+    /// An `address` or `contract` value.
+    Address(Address),
+
+    Bool(bool),
+
+    /// A `bytesN` value.
+    FixedBytes(Vec<u8>),
+
+    /// A `bytes` value.
+    Bytes(Vec<u8>),
+
+    String(String),
+}
+
+impl EthereumSolcStorageLayout {
+    /// Parses the JSON storage layout.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        serde_json::from_str(json).map_err(|e| EthereumError::decode("storage layout", e).into())
+    }
+
+    /// Resolves a variable from its path, such as `withdrawals[42].receiver`.
+    ///
+    /// Struct members are accessed with `.member`, and array elements and mapping entries with
+    /// `[key]`. Keys are parsed as the mapping key type: numbers in decimal or `0x` prefixed hex,
+    /// hex addresses and `bytesN`, `true` or `false`, and quoted strings.
+    pub fn resolve(&self, path: &str) -> anyhow::Result<EthereumSolcVariable<'_>> {
+        let mut segments = Segment::parse(path)?.into_iter();
+
+        let entry = match segments.next() {
+            Some(Segment::Member(label)) => self
+                .storage
+                .iter()
+                .find(|e| e.label == label)
+                .ok_or_else(|| EthereumError::config(format_args!("unknown variable `{label}`")))?,
+            _ => return Err(EthereumError::config(format_args!("invalid path `{path}`")).into()),
+        };
+
+        let mut ty = self.ty(&entry.ty)?;
+        let mut position =
+            EthereumSlotPosition::new(entry.slot, entry.offset, ty.number_of_bytes.clamp(1, 32))?;
+
+        for segment in segments {
+            let (slot, offset, id) = match (segment, ty.encoding) {
+                (Segment::Member(label), EthereumSolcEncoding::Inplace) => {
+                    let member = ty
+                        .members
+                        .iter()
+                        .flatten()
+                        .find(|m| m.label == label)
+                        .ok_or_else(|| {
+                            EthereumError::config(format_args!(
+                                "unknown member `{label}` of `{path}`"
+                            ))
+                        })?;
+
+                    (
+                        position.slot() + member.slot,
+                        member.offset,
+                        member.ty.as_str(),
+                    )
+                }
+
+                (Segment::Index(key), EthereumSolcEncoding::Mapping) => {
+                    let key_ty = self.ty(ty.key.as_deref().unwrap_or_default())?;
+                    let key = mapping_key(key_ty, key)?;
+                    let id = ty.value.as_ref();
+
+                    (key.slot(position.slot()), 0, Self::id(id)?)
+                }
+
+                (Segment::Index(index), EthereumSolcEncoding::Inplace)
+                | (Segment::Index(index), EthereumSolcEncoding::DynamicArray)
+                    if ty.base.is_some() =>
+                {
+                    let index: u64 = index.parse().map_err(|_| {
+                        EthereumError::config(format_args!("invalid array index `{index}`"))
+                    })?;
+
+                    let id = Self::id(ty.base.as_ref())?;
+                    let size = self.ty(id)?.number_of_bytes;
+
+                    let layout = if ty.encoding == EthereumSolcEncoding::DynamicArray {
+                        EthereumArrayLayout::dynamic(position.slot(), size)
+                    } else {
+                        let len = ty
+                            .label
+                            .rsplit_once('[')
+                            .and_then(|(_, len)| len.strip_suffix(']')?.parse::<u64>().ok())
+                            .ok_or_else(|| {
+                                EthereumError::decode(
+                                    "storage layout",
+                                    format_args!("invalid array type `{}`", ty.label),
+                                )
+                            })?;
+
+                        if index >= len {
+                            return Err(EthereumError::config(format_args!(
+                                "index {index} out of bounds of `{path}`"
+                            ))
+                            .into());
+                        }
+
+                        EthereumArrayLayout::fixed(position.slot(), size)
+                    };
+
+                    let element = layout.position(index);
+
+                    (element.slot(), element.offset(), id)
+                }
+
+                _ => {
+                    return Err(EthereumError::config(format_args!(
+                        "`{path}` doesn't match the type `{}`",
+                        ty.label
+                    ))
+                    .into())
+                }
+            };
+
+            ty = self.ty(id)?;
+            position = EthereumSlotPosition::new(slot, offset, ty.number_of_bytes.clamp(1, 32))?;
+        }
+
+        Ok(EthereumSolcVariable { position, ty })
+    }
+
+    /// Starts the storage proof arguments of the variables of the layout.
+    pub fn storage(&self) -> EthereumSolcStorageBuilder<'_> {
+        EthereumSolcStorageBuilder {
+            layout: self,
+            values: Vec::new(),
+        }
+    }
+
+    /// Decodes the variable at `path` from the proven storage values.
+    ///
+    /// `T[]` arrays are decoded as their length.
+    pub fn decode(
+        &self,
+        path: &str,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<EthereumSolcValue> {
+        let EthereumSolcVariable { position, ty } = self.resolve(path)?;
+        let word = Ethereum::read_slot(storage, position.slot())?;
+
+        match ty.encoding {
+            EthereumSolcEncoding::DynamicArray => {
+                return Ok(EthereumSolcValue::Uint(U256::from_be_bytes(word)))
+            }
+
+            EthereumSolcEncoding::Bytes => {
+                let bytes = Ethereum::read_bytes(storage, position.slot())?;
+
+                return match ty.label.as_str() {
+                    "string" => String::from_utf8(bytes)
+                        .map(EthereumSolcValue::String)
+                        .map_err(|e| EthereumError::decode("string value", e).into()),
+                    _ => Ok(EthereumSolcValue::Bytes(bytes)),
+                };
+            }
+
+            EthereumSolcEncoding::Inplace | EthereumSolcEncoding::Mapping => (),
+        }
+
+        let value = position.read(&word);
+
+        let value = match ValueKind::of(ty) {
+            Some(ValueKind::Bool) => EthereumSolcValue::Bool(value.iter().any(|b| *b != 0)),
+            Some(ValueKind::Address) => EthereumSolcValue::Address(Address::from_slice(value)),
+            Some(ValueKind::Uint) => EthereumSolcValue::Uint(U256::from_be_slice(value)),
+            Some(ValueKind::Int) => {
+                let fill = if value[0] & 0x80 != 0 { 0xff } else { 0 };
+                let mut word = [fill; 32];
+
+                word[32 - value.len()..].copy_from_slice(value);
+
+                EthereumSolcValue::Int(I256::from_be_bytes(word))
+            }
+            Some(ValueKind::FixedBytes) => EthereumSolcValue::FixedBytes(value.to_vec()),
+            None => return Err(not_a_value(path, ty).into()),
+        };
+
+        Ok(value)
+    }
+
+    fn ty(&self, id: &str) -> Result<&EthereumSolcType, EthereumError> {
+        self.types.get(id).ok_or_else(|| {
+            EthereumError::decode("storage layout", format_args!("unknown type `{id}`"))
+        })
+    }
+
+    fn id(id: Option<&String>) -> Result<&str, EthereumError> {
+        id.map(String::as_str)
+            .ok_or_else(|| EthereumError::decode("storage layout", "incomplete type definition"))
+    }
+}
+
+/// Builds the storage proof arguments of variables resolved from their paths.
+///
+/// Values sharing a slot are packed into the same proof argument, so every variable of a packed
+/// slot should be provided.
+#[derive(Debug, Clone)]
+pub struct EthereumSolcStorageBuilder<'a> {
+    layout: &'a EthereumSolcStorageLayout,
+    values: Vec<(String, Vec<u8>)>,
+}
+
+impl EthereumSolcStorageBuilder<'_> {
+    /// Adds the variable at `path` with its value.
+    ///
+    /// Value types are given big-endian, and `string` and `bytes` as their contents. `T[]`
+    /// arrays take their length.
+    pub fn with_value<T>(mut self, path: &str, value: T) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        self.values
+            .push((path.to_string(), value.as_ref().to_vec()));
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<Vec<EthereumStorageProofArg>> {
+        let mut words = EthereumStorageWords::default();
+
+        for (path, value) in self.values {
+            let EthereumSolcVariable { position, ty } = self.layout.resolve(&path)?;
+
+            match ty.encoding {
+                EthereumSolcEncoding::Bytes => words.write_bytes(position.slot(), &value),
+                EthereumSolcEncoding::DynamicArray => words.write(position, &value),
+                _ if ValueKind::of(ty).is_some() => words.write(position, &value),
+                _ => return Err(not_a_value(&path, ty).into()),
+            }
+        }
+
+        Ok(words.build())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment<'a> {
+    Member(&'a str),
+    Index(&'a str),
+}
+
+impl<'a> Segment<'a> {
+    fn parse(path: &'a str) -> Result<Vec<Self>, EthereumError> {
+        let invalid = || EthereumError::config(format_args!("invalid path `{path}`"));
+
+        let mut segments = Vec::new();
+        let mut rest = path.trim();
+
+        while !rest.is_empty() {
+            if let Some(index) = rest.strip_prefix('[') {
+                let end = match index.strip_prefix('"') {
+                    Some(quoted) => quoted.find('"').map(|i| i + 2).ok_or_else(invalid)?,
+                    None => index.find(']').ok_or_else(invalid)?,
+                };
+
+                if !index[end..].starts_with(']') {
+                    return Err(invalid());
+                }
+
+                segments.push(Self::Index(index[..end].trim()));
+                rest = &index[end + 1..];
+                continue;
+            }
+
+            let member = match rest.strip_prefix('.') {
+                Some(member) if !segments.is_empty() => member,
+                None if segments.is_empty() => rest,
+                _ => return Err(invalid()),
+            };
+
+            let end = member.find(['.', '[']).unwrap_or(member.len());
+
+            if end == 0 {
+                return Err(invalid());
+            }
+
+            segments.push(Self::Member(&member[..end]));
+            rest = &member[end..];
+        }
+
+        Ok(segments)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Bool,
+    Address,
+    Uint,
+    Int,
+    FixedBytes,
+}
+
+impl ValueKind {
+    fn of(ty: &EthereumSolcType) -> Option<Self> {
+        if ty.encoding != EthereumSolcEncoding::Inplace || ty.members.is_some() || ty.base.is_some()
+        {
+            return None;
+        }
+
+        let label = ty.label.as_str();
+
+        match label {
+            "bool" => Some(Self::Bool),
+            "address" | "address payable" => Some(Self::Address),
+            _ if label.starts_with("contract ") => Some(Self::Address),
+            _ if label.starts_with("enum ") || label.starts_with("uint") => Some(Self::Uint),
+            _ if label.starts_with("int") => Some(Self::Int),
+            _ if label.starts_with("bytes") => Some(Self::FixedBytes),
+            _ => None,
+        }
+    }
+}
+
+fn mapping_key(ty: &EthereumSolcType, key: &str) -> Result<EthereumMappingKey, EthereumError> {
+    let invalid = || EthereumError::config(format_args!("invalid `{}` key `{key}`", ty.label));
+
+    if ty.encoding == EthereumSolcEncoding::Bytes {
+        if let Some(key) = key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+            return Ok(key.into());
+        }
+
+        return hex::decode(key)
+            .map(EthereumMappingKey::from)
+            .map_err(|_| invalid());
+    }
+
+    let key = match ValueKind::of(ty).ok_or_else(invalid)? {
+        ValueKind::Bool => match key {
+            "true" => true.into(),
+            "false" => false.into(),
+            _ => return Err(invalid()),
+        },
+        ValueKind::Address => Address::from_str(key).map_err(|_| invalid())?.into(),
+        ValueKind::Uint => U256::from_str(key).map_err(|_| invalid())?.into(),
+        ValueKind::Int => I256::from_str(key).map_err(|_| invalid())?.into(),
+        ValueKind::FixedBytes => {
+            let bytes = hex::decode(key).map_err(|_| invalid())?;
+
+            if bytes.len() != ty.number_of_bytes {
+                return Err(invalid());
+            }
+
+            B256::right_padding_from(&bytes).into()
+        }
+    };
+
+    Ok(key)
+}
+
+fn parse<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = String::deserialize(deserializer)?;
+
+    value
+        .parse()
+        .map_err(|e| serde::de::Error::custom(format!("invalid number `{value}`: {e}")))
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+fn not_a_value(path: &str, ty: &EthereumSolcType) -> EthereumError {
+    EthereumError::config(format_args!(
+        "`{path}` of type `{}` is not a value",
+        ty.label
+    ))
+}
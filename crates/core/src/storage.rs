@@ -0,0 +1,336 @@
+use alloc::{string::String, vec::Vec};
+use alloy_primitives::{Address, Bytes, FixedBytes, Signed, Uint, U256};
+
+use crate::{
+    Ethereum, EthereumArrayLayout, EthereumError, EthereumSlotPosition, EthereumStorageProofArg,
+    EthereumStorageWords,
+};
+
+/// A type laid out in contract storage as Solidity does.
+///
+/// Implemented for the Rust types of the Solidity value types, `string`, `bytes` and arrays.
+/// Structs implement it with `#[derive(StorageLayout)]`, so the same definition both encodes the
+/// storage proof arguments in the controller and decodes the proven storage in the circuit.
+pub trait EthereumStorageLayout: Sized {
+    /// Size of the type in bytes.
+    const SIZE: usize;
+
+    /// `true` if the type is packed with the contiguous values; structs, arrays, `string` and
+    /// `bytes` always start a new slot.
+    const PACKED: bool;
+
+    /// Writes the value at its position.
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords);
+
+    /// Reads the value at its position from the proven storage values.
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self>;
+
+    /// Returns the storage proof arguments of the value stored at `slot`.
+    fn to_storage(&self, slot: U256) -> Vec<EthereumStorageProofArg> {
+        let mut words = EthereumStorageWords::default();
+
+        self.store(Self::position(slot), &mut words);
+
+        words.build()
+    }
+
+    /// Decodes the value stored at `slot` from the proven storage values.
+    fn from_storage(slot: U256, storage: &[EthereumStorageProofArg]) -> anyhow::Result<Self> {
+        Self::load(Self::position(slot), storage)
+    }
+
+    /// Position of the value stored at `slot`.
+    fn position(slot: U256) -> EthereumSlotPosition {
+        EthereumSlotPosition::fitting(slot, 0, Self::SIZE.clamp(1, 32))
+    }
+
+    /// Number of slots used by the value.
+    fn slots() -> u64 {
+        Self::SIZE.div_ceil(32).max(1) as u64
+    }
+}
+
+/// Positions the members of a struct in storage, in declaration order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthereumStorageCursor {
+    slot: U256,
+    offset: usize,
+}
+
+impl EthereumStorageCursor {
+    /// Starts at the first slot of the struct.
+    pub fn new(slot: U256) -> Self {
+        Self { slot, offset: 0 }
+    }
+
+    /// Returns the position of the next member.
+    pub fn next_member<T: EthereumStorageLayout>(&mut self) -> EthereumSlotPosition {
+        if self.offset > 0 && (!T::PACKED || self.offset + T::SIZE > 32) {
+            self.slot += U256::ONE;
+            self.offset = 0;
+        }
+
+        let position = EthereumSlotPosition::fitting(self.slot, self.offset, T::SIZE.clamp(1, 32));
+
+        if T::PACKED {
+            self.offset += T::SIZE;
+        } else {
+            self.slot += U256::from(T::SIZE.div_ceil(32));
+        }
+
+        position
+    }
+
+    /// Size in bytes of a struct with the `(size, packed)` members.
+    pub const fn size_of(members: &[(usize, bool)]) -> usize {
+        let mut slots = 0;
+        let mut offset = 0;
+        let mut i = 0;
+
+        while i < members.len() {
+            let (size, packed) = members[i];
+
+            if offset > 0 && (!packed || offset + size > 32) {
+                slots += 1;
+                offset = 0;
+            }
+
+            if packed {
+                offset += size;
+            } else {
+                slots += size.div_ceil(32);
+            }
+
+            i += 1;
+        }
+
+        if offset > 0 {
+            slots += 1;
+        }
+
+        slots * 32
+    }
+}
+
+impl EthereumStorageLayout for bool {
+    const SIZE: usize = 1;
+    const PACKED: bool = true;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        words.write(position, &[*self as u8]);
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        let word = Ethereum::read_slot(storage, position.slot())?;
+
+        Ok(position.read(&word).iter().any(|b| *b != 0))
+    }
+}
+
+macro_rules! impl_int_layout {
+    ($($t:ty),*) => {
+        $(
+            impl EthereumStorageLayout for $t {
+                const SIZE: usize = core::mem::size_of::<$t>();
+                const PACKED: bool = true;
+
+                fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+                    words.write(position, &self.to_be_bytes());
+                }
+
+                fn load(
+                    position: EthereumSlotPosition,
+                    storage: &[EthereumStorageProofArg],
+                ) -> anyhow::Result<Self> {
+                    let word = Ethereum::read_slot(storage, position.slot())?;
+                    let mut bytes = [0; core::mem::size_of::<$t>()];
+
+                    bytes.copy_from_slice(position.read(&word));
+
+                    Ok(<$t>::from_be_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_int_layout!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl<const BITS: usize, const LIMBS: usize> EthereumStorageLayout for Uint<BITS, LIMBS> {
+    const SIZE: usize = BITS / 8;
+    const PACKED: bool = true;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        words.write(position, &self.to_be_bytes_vec());
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        let word = Ethereum::read_slot(storage, position.slot())?;
+
+        Self::try_from_be_slice(position.read(&word)).ok_or_else(|| {
+            EthereumError::decode(
+                "storage value",
+                format_args!("slot {} is not a valid uint{BITS}", position.slot()),
+            )
+            .into()
+        })
+    }
+}
+
+impl<const BITS: usize, const LIMBS: usize> EthereumStorageLayout for Signed<BITS, LIMBS> {
+    const SIZE: usize = BITS / 8;
+    const PACKED: bool = true;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        self.into_raw().store(position, words);
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        Uint::load(position, storage).map(Self::from_raw)
+    }
+}
+
+impl EthereumStorageLayout for Address {
+    const SIZE: usize = 20;
+    const PACKED: bool = true;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        words.write(position, self.as_slice());
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        let word = Ethereum::read_slot(storage, position.slot())?;
+
+        Ok(Address::from_slice(position.read(&word)))
+    }
+}
+
+impl<const N: usize> EthereumStorageLayout for FixedBytes<N> {
+    const SIZE: usize = N;
+    const PACKED: bool = true;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        words.write(position, self.as_slice());
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        let word = Ethereum::read_slot(storage, position.slot())?;
+
+        Ok(Self::from_slice(position.read(&word)))
+    }
+}
+
+impl EthereumStorageLayout for String {
+    const SIZE: usize = 32;
+    const PACKED: bool = false;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        words.write_bytes(position.slot(), self.as_bytes());
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        let bytes = Ethereum::read_bytes(storage, position.slot())?;
+
+        String::from_utf8(bytes).map_err(|e| EthereumError::decode("string value", e).into())
+    }
+}
+
+impl EthereumStorageLayout for Bytes {
+    const SIZE: usize = 32;
+    const PACKED: bool = false;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        words.write_bytes(position.slot(), self);
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        Ethereum::read_bytes(storage, position.slot()).map(Bytes::from)
+    }
+}
+
+/// A `T[]` array, with its length at the slot and the elements at its hash.
+impl<T: EthereumStorageLayout> EthereumStorageLayout for Vec<T> {
+    const SIZE: usize = 32;
+    const PACKED: bool = false;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        let layout = EthereumArrayLayout::dynamic(position.slot(), T::SIZE);
+
+        U256::from(self.len()).store(position, words);
+
+        for (i, element) in self.iter().enumerate() {
+            element.store(layout.position(i as u64), words);
+        }
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        let layout = EthereumArrayLayout::dynamic(position.slot(), T::SIZE);
+        let len = U256::load(position, storage)?;
+        let len: u64 = len
+            .try_into()
+            .map_err(|_| EthereumError::decode("array length", len))?;
+
+        (0..len)
+            .map(|i| T::load(layout.position(i), storage))
+            .collect()
+    }
+}
+
+/// A `T[N]` array, with the elements from the slot.
+impl<T: EthereumStorageLayout, const N: usize> EthereumStorageLayout for [T; N] {
+    const SIZE: usize = if T::SIZE >= 32 {
+        N * T::SIZE.div_ceil(32) * 32
+    } else {
+        N.div_ceil(32 / T::SIZE) * 32
+    };
+    const PACKED: bool = false;
+
+    fn store(&self, position: EthereumSlotPosition, words: &mut EthereumStorageWords) {
+        let layout = EthereumArrayLayout::fixed(position.slot(), T::SIZE);
+
+        for (i, element) in self.iter().enumerate() {
+            element.store(layout.position(i as u64), words);
+        }
+    }
+
+    fn load(
+        position: EthereumSlotPosition,
+        storage: &[EthereumStorageProofArg],
+    ) -> anyhow::Result<Self> {
+        let layout = EthereumArrayLayout::fixed(position.slot(), T::SIZE);
+        let elements = (0..N as u64)
+            .map(|i| T::load(layout.position(i), storage))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        elements
+            .try_into()
+            .map_err(|v: Vec<T>| EthereumError::decode("array length", v.len()).into())
+    }
+}
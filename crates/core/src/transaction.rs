@@ -0,0 +1,127 @@
+use alloc::vec::Vec;
+use msgpacker::{MsgPacker, Packable as _, Unpackable as _};
+use serde::{Deserialize, Serialize};
+use valence_coprocessor::Hash;
+
+use crate::{EthereumHeaderProof, EthereumNetwork, EthereumStateProof};
+
+/// Inclusion proofs of the transactions of an ancestor block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumTransactionProof {
+    pub state_root: Hash,
+    /// Header of the block containing the transactions.
+    pub block: EthereumHeaderProof,
+    /// Transactions proven against the transactions root of the block.
+    pub transactions: Vec<EthereumTransactionInclusionProof>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumTransactionInclusionProof {
+    /// Index of the transaction in the block.
+    pub index: u64,
+
+    /// EIP-2718 encoded transaction.
+    pub transaction: Vec<u8>,
+
+    /// The Merkle transaction proof.
+    pub proof: Vec<Vec<u8>>,
+}
+
+impl EthereumTransactionProof {
+    /// Wire format version, following [EthereumStateProof::MAGIC].
+    pub const VERSION: u8 = 0x92;
+
+    /// Serializes the proof into its versioned wire format.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(512);
+
+        bytes.push(EthereumStateProof::MAGIC);
+        bytes.push(Self::VERSION);
+
+        self.pack(&mut bytes);
+
+        bytes
+    }
+
+    /// Deserializes a transaction proof.
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, msgpacker::Error> {
+        match bytes {
+            [EthereumStateProof::MAGIC, Self::VERSION, proof @ ..] => Ok(Self::unpack(proof)?.1),
+            _ => Err(msgpacker::Error::UnexpectedFormatTag),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, MsgPacker)]
+pub struct EthereumProvenTransaction {
+    /// Number of the block containing the transaction.
+    pub block_number: u64,
+
+    /// Hash of the block containing the transaction.
+    pub block_hash: Hash,
+
+    /// Index of the transaction in the block.
+    pub transaction_index: u64,
+
+    /// Transaction hash.
+    pub hash: Hash,
+
+    /// EIP-2718 transaction type.
+    pub tx_type: u8,
+
+    /// Chain ID, absent for pre EIP-155 legacy transactions.
+    pub chain_id: Option<u64>,
+
+    /// Sender nonce.
+    pub nonce: u64,
+
+    /// Gas limit of the transaction.
+    pub gas_limit: u64,
+
+    /// Recipient address, absent for contract creations.
+    pub to: Option<Vec<u8>>,
+
+    /// Big-endian encoded transferred value.
+    pub value: [u8; 32],
+
+    /// Transaction calldata.
+    pub input: Vec<u8>,
+
+    /// Hash signed by the sender, to recover its address.
+    pub signature_hash: Hash,
+
+    /// Sender signature as `r`, `s` and `v`.
+    pub signature: Vec<u8>,
+
+    /// EIP-2718 encoded transaction, to decode type specific fields.
+    pub transaction: Vec<u8>,
+
+    /// User payload.
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthereumTransactionProofArgs {
+    /// Validated block number.
+    pub block: u64,
+
+    /// State root [Hash] of the validated block.
+    pub root: Hash,
+
+    /// Number of the block containing the transactions.
+    pub target: u64,
+
+    /// Indexes of the transactions in the target block.
+    pub transactions: Vec<u64>,
+
+    /// Hashes of transactions of the target block, proven after the indexed ones.
+    #[serde(default)]
+    pub hashes: Vec<Hash>,
+
+    /// Payload to be forwarded to the circuit.
+    pub payload: Vec<u8>,
+
+    /// Network of the proven block.
+    #[serde(default)]
+    pub network: EthereumNetwork,
+}
@@ -0,0 +1,280 @@
+use alloc::{format, string::String, vec::Vec};
+use alloy_primitives::{keccak256, Address, B256, U256};
+use alloy_rlp::{Decodable as _, Encodable as _, EMPTY_STRING_CODE};
+use alloy_rpc_types_eth::Account;
+use alloy_trie::{
+    nodes::{BranchNode, RlpNode, TrieNode},
+    Nibbles, EMPTY_ROOT_HASH,
+};
+use valence_coprocessor::Hash;
+
+use crate::{
+    Ethereum, EthereumAccountProof, EthereumCodeProof, EthereumError, EthereumProvenAccount,
+    EthereumProvenAccounts,
+};
+
+impl EthereumAccountProof {
+    /// Verifies the account and its storage proofs against the state root.
+    pub fn verify(&self, state_root: B256) -> Result<(), EthereumError> {
+        let key = Nibbles::unpack(keccak256(&self.account));
+        let encoded_account = self.encoded_account()?;
+
+        verify_trie_path(
+            state_root,
+            &key,
+            encoded_account.as_deref(),
+            with_references(&self.account_proof),
+        )
+        .map_err(|e| self.account_error(e))?;
+
+        let root = self.storage_root.into();
+
+        for p in self.storage_proofs.iter() {
+            let key = Nibbles::unpack(keccak256(&p.key));
+
+            verify_trie_path(root, &key, p.value.as_deref(), with_references(&p.proof))
+                .map_err(|e| self.storage_error(&p.key, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// RLP encodes the account as stored in the state trie, or `None` if it doesn't exist.
+    pub(crate) fn encoded_account(&self) -> Result<Option<Vec<u8>>, EthereumError> {
+        if !self.exists {
+            if !self.storage_proofs.is_empty() {
+                return Err(
+                    self.account_error("non-existent account cannot have storage proofs".into())
+                );
+            }
+
+            return Ok(None);
+        }
+
+        let mut encoded_account = Vec::new();
+        Account {
+            nonce: self.nonce,
+            balance: U256::from_be_bytes(self.balance),
+            storage_root: self.storage_root.into(),
+            code_hash: self.code_hash.into(),
+        }
+        .encode(&mut encoded_account);
+
+        Ok(Some(encoded_account))
+    }
+
+    pub(crate) fn account_error(&self, reason: String) -> EthereumError {
+        EthereumError::AccountProof {
+            account: self.account.clone(),
+            reason,
+        }
+    }
+
+    pub(crate) fn storage_error(&self, key: &[u8], reason: String) -> EthereumError {
+        EthereumError::StorageProof {
+            account: self.account.clone(),
+            key: key.to_vec(),
+            reason,
+        }
+    }
+}
+
+impl EthereumCodeProof {
+    /// Verifies the bytecode against the proven code hash of its account.
+    pub fn verify(&self, code_hash: &Hash) -> Result<(), EthereumError> {
+        if keccak256(&self.code).0 != *code_hash {
+            return Err(EthereumError::CodeProof {
+                account: self.account.clone(),
+                reason: "the bytecode doesn't match the code hash".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl EthereumProvenAccount {
+    /// Returns `true` if the proven bytecode pushes the function `selector`.
+    ///
+    /// `false` if the bytecode wasn't requested.
+    pub fn has_selector(&self, selector: [u8; 4]) -> bool {
+        self.code
+            .as_deref()
+            .is_some_and(|code| Ethereum::code_has_selector(code, selector))
+    }
+}
+
+impl EthereumProvenAccounts {
+    /// Returns the proven account of `address`, if it is part of the proof.
+    pub fn get(&self, address: &Address) -> Option<&EthereumProvenAccount> {
+        self.accounts
+            .iter()
+            .find(|a| a.account == address.as_slice())
+    }
+}
+
+impl Ethereum {
+    /// Returns `true` if the bytecode pushes `selector` with `PUSH4`, as the function dispatcher
+    /// of a contract does.
+    ///
+    /// The immediate data of the push instructions is skipped, so it isn't mistaken for code.
+    pub fn code_has_selector(code: &[u8], selector: [u8; 4]) -> bool {
+        const PUSH1: u8 = 0x60;
+        const PUSH4: u8 = 0x63;
+        const PUSH32: u8 = 0x7f;
+
+        let mut i = 0;
+
+        while i < code.len() {
+            let op = code[i];
+
+            if op == PUSH4 && code.get(i + 1..i + 5) == Some(&selector[..]) {
+                return true;
+            }
+
+            i += 1;
+
+            if (PUSH1..=PUSH32).contains(&op) {
+                i += (op - PUSH1) as usize + 1;
+            }
+        }
+
+        false
+    }
+}
+
+/// Pairs the nodes of a proof with their references.
+fn with_references(proof: &[Vec<u8>]) -> impl Iterator<Item = (&[u8], RlpNode)> {
+    proof.iter().map(|n| (n.as_slice(), RlpNode::from_rlp(n)))
+}
+
+/// Verifies the path of `key` against `root`, mirroring [alloy_trie::proof::verify_proof].
+///
+/// The nodes of the path come with their references. Unlike [alloy_trie::proof::verify_proof],
+/// the absence of the key is only accepted if the path ends on a branch without the child of the
+/// key, or on a leaf or extension diverging from it; a path stopping at an unresolved child
+/// proves nothing.
+pub(crate) fn verify_trie_path<'a, I>(
+    root: B256,
+    key: &Nibbles,
+    expected: Option<&[u8]>,
+    path: I,
+) -> Result<(), String>
+where
+    I: IntoIterator<Item = (&'a [u8], RlpNode)>,
+{
+    let mut path = path.into_iter().peekable();
+
+    if path
+        .peek()
+        .is_none_or(|(node, _)| *node == [EMPTY_STRING_CODE])
+    {
+        if root != EMPTY_ROOT_HASH {
+            return Err("root mismatch on empty trie".into());
+        }
+
+        if expected.is_some() {
+            return Err("value mismatch on empty trie".into());
+        }
+
+        return Ok(());
+    }
+
+    let mut walked_path = Nibbles::with_capacity(key.len());
+    let mut last = Some(Walked::Node(RlpNode::word_rlp(&root)));
+
+    for (node, reference) in path {
+        if Some(reference.as_slice()) != last.as_ref().map(Walked::as_slice) {
+            return Err(format!("node mismatch at path {walked_path:?}"));
+        }
+
+        last = match decode_node(node)? {
+            TrieNode::Branch(branch) => descend(branch, &mut walked_path, key)?,
+            TrieNode::Extension(extension) => {
+                walked_path.extend_from_slice(&extension.key);
+                Some(Walked::Node(extension.child))
+            }
+            TrieNode::Leaf(leaf) => {
+                walked_path.extend_from_slice(&leaf.key);
+                Some(Walked::Value(leaf.value))
+            }
+            TrieNode::EmptyRoot => return Err("unexpected empty root".into()),
+        };
+    }
+
+    // a child still to be resolved on the path of the key may hold it
+    if matches!(last, Some(Walked::Node(_))) && key.starts_with(&walked_path) {
+        return Err(format!("the proof ends before resolving {key:?}"));
+    }
+
+    let last = last.filter(|_| &walked_path == key);
+
+    if last.as_ref().map(Walked::as_slice) != expected {
+        return Err(format!("value mismatch at path {key:?}"));
+    }
+
+    Ok(())
+}
+
+/// The expected reference of the next node of a path, or the value it resolved to.
+enum Walked {
+    Node(RlpNode),
+    Value(Vec<u8>),
+}
+
+impl Walked {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Node(node) => node.as_slice(),
+            Self::Value(value) => value,
+        }
+    }
+}
+
+fn decode_node(node: &[u8]) -> Result<TrieNode, String> {
+    TrieNode::decode(&mut &node[..]).map_err(|e| format!("invalid trie node: {e}"))
+}
+
+fn descend(
+    mut branch: BranchNode,
+    walked_path: &mut Nibbles,
+    key: &Nibbles,
+) -> Result<Option<Walked>, String> {
+    let Some(next) = key.get(walked_path.len()).copied() else {
+        return Ok(None);
+    };
+
+    if !branch.state_mask.is_bit_set(next) {
+        return Ok(None);
+    }
+
+    let index = (0..next)
+        .filter(|i| branch.state_mask.is_bit_set(*i))
+        .count();
+
+    walked_path.push(next);
+
+    let child = branch.stack.remove(index);
+
+    if child.len() == B256::len_bytes() + 1 {
+        return Ok(Some(Walked::Node(child)));
+    }
+
+    // the child is encoded in-place
+    match decode_node(&child)? {
+        TrieNode::Branch(branch) => descend(branch, walked_path, key),
+        TrieNode::Extension(extension) => {
+            walked_path.extend_from_slice(&extension.key);
+
+            match decode_node(&extension.child)? {
+                TrieNode::Branch(branch) => descend(branch, walked_path, key),
+                _ => Err("unexpected in-place extension child".into()),
+            }
+        }
+        TrieNode::Leaf(leaf) => {
+            walked_path.extend_from_slice(&leaf.key);
+            Ok(Some(Walked::Value(leaf.value)))
+        }
+        TrieNode::EmptyRoot => Err("unexpected empty root".into()),
+    }
+}
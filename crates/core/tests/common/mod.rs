@@ -0,0 +1,116 @@
+//! Synthetic tries and RPC responses shared by the tests.
+
+#![allow(dead_code)]
+
+use alloy_consensus::Header;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_trie::{Nibbles, TrieAccount};
+use serde_json::{json, Value};
+use valence_coprocessor_ethereum::Ethereum;
+
+/// Builds a trie, returning its root and the proofs for each of the `targets`.
+pub fn trie(leaves: Vec<(Nibbles, Vec<u8>)>, targets: &[Nibbles]) -> (B256, Vec<Vec<Vec<u8>>>) {
+    Ethereum::trie_proofs(leaves, targets.to_vec())
+}
+
+/// Builds a trie keyed by the RLP encoded indexes of the values, as the receipts trie.
+pub fn ordered_trie(values: &[Vec<u8>], targets: &[u64]) -> (B256, Vec<Vec<Vec<u8>>>) {
+    let leaves = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (Nibbles::unpack(alloy_rlp::encode(i)), v.clone()))
+        .collect();
+
+    let targets: Vec<_> = targets
+        .iter()
+        .map(|i| Nibbles::unpack(alloy_rlp::encode(*i)))
+        .collect();
+
+    trie(leaves, &targets)
+}
+
+/// Creates a state where the history contract holds the hash of the header.
+///
+/// Returns the state root with the `eth_getBlockByNumber` response of the header and the
+/// `eth_getProof` response of its history slot.
+pub fn history(header: &Header) -> (B256, Value, Value) {
+    let hash = header.hash_slow();
+    let slot = U256::from(header.number % Ethereum::HISTORY_SERVE_WINDOW);
+    let filler = slot + U256::ONE;
+
+    let slot_key = Nibbles::unpack(keccak256(slot.to_be_bytes::<32>()));
+    let (storage_root, mut storage_proofs) = trie(
+        vec![
+            (
+                slot_key.clone(),
+                alloy_rlp::encode(U256::from_be_bytes(hash.0)),
+            ),
+            (
+                Nibbles::unpack(keccak256(filler.to_be_bytes::<32>())),
+                alloy_rlp::encode(U256::from(0xdead)),
+            ),
+        ],
+        &[slot_key],
+    );
+
+    let account = TrieAccount {
+        nonce: 1,
+        balance: U256::ZERO,
+        storage_root,
+        code_hash: keccak256(b"history"),
+    };
+
+    let account_key = Nibbles::unpack(keccak256(Ethereum::HISTORY_STORAGE_ADDRESS));
+    let (state_root, mut account_proofs) = trie(
+        vec![
+            (account_key.clone(), alloy_rlp::encode(account)),
+            (
+                Nibbles::unpack(keccak256([0x11; 20])),
+                alloy_rlp::encode(TrieAccount::default()),
+            ),
+        ],
+        &[account_key],
+    );
+
+    let storage = json!([{
+        "key": B256::from(slot),
+        "value": U256::from_be_bytes(hash.0),
+        "proof": bytes(storage_proofs.pop().unwrap()),
+    }]);
+
+    let history = account_response(
+        Ethereum::HISTORY_STORAGE_ADDRESS,
+        &account,
+        account_proofs.pop().unwrap(),
+        storage,
+    );
+
+    let mut block = serde_json::to_value(header).unwrap();
+
+    block["hash"] = json!(hash);
+
+    (state_root, block, history)
+}
+
+/// Mocks the `eth_getProof` response of an account.
+pub fn account_response(
+    address: [u8; 20],
+    account: &TrieAccount,
+    account_proof: Vec<Vec<u8>>,
+    storage_proof: Value,
+) -> Value {
+    json!({
+        "address": Address::from(address),
+        "balance": account.balance,
+        "codeHash": account.code_hash,
+        "nonce": format!("{:#x}", account.nonce),
+        "storageHash": account.storage_root,
+        "accountProof": bytes(account_proof),
+        "storageProof": storage_proof,
+    })
+}
+
+/// Converts trie nodes into their JSON representation.
+pub fn bytes(proof: Vec<Vec<u8>>) -> Vec<Bytes> {
+    proof.into_iter().map(Bytes::from).collect()
+}
@@ -1,13 +1,24 @@
-use alloy_primitives::U256;
+use alloy_primitives::{keccak256, Address, B256, U256};
 use alloy_rlp::{RlpDecodable, RlpEncodable};
+use alloy_trie::{Nibbles, TrieAccount, KECCAK_EMPTY};
+use msgpacker::Packable as _;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use valence_coprocessor::{DomainCircuit as _, Hash};
+use serde_json::{json, Value};
+use valence_coprocessor::{DomainCircuit as _, Hash, StateProof};
 use valence_coprocessor_ethereum::{
-    controller::EthereumStorageLayoutBuilder, Ethereum, EthereumStateProofArgs,
-    EthereumStorageProofArg,
+    controller::{
+        EthereumMemoryProvider, EthereumProviderConfig, EthereumStateProofArgsBuilder,
+        EthereumStorageLayoutBuilder,
+    },
+    Ethereum, EthereumAccountProof, EthereumAccountProofArgs, EthereumBlockRange,
+    EthereumCodeProof, EthereumCompactStateProof, EthereumError, EthereumMappingKey,
+    EthereumNetwork, EthereumProofMode, EthereumStateProof, EthereumStateProofArgs,
+    EthereumStateProofV0, EthereumStorageLayout as _, EthereumStorageProof,
+    EthereumStorageProofArg, EthereumStorageValues, StorageLayout,
 };
 
+mod common;
+
 #[test]
 fn encode_and_verify_proofs_works() {
     let cases = [
@@ -45,6 +56,13 @@ fn encode_and_verify_proofs_works() {
             root,
             storage,
             payload: payload.to_vec(),
+            mode: EthereumProofMode::Storage,
+            accounts: vec![],
+            compact: false,
+            values: EthereumStorageValues::Provided,
+            network: EthereumNetwork::Mainnet,
+            code: false,
+            fetched: vec![],
         };
 
         let proof = Ethereum::encode_proof(proof, args).unwrap();
@@ -79,8 +97,841 @@ fn encode_and_verify_proofs_works() {
     }
 }
 
+#[test]
+fn derived_layouts_round_trip_proven_storage() {
+    let cases = [
+        &include_bytes!("../../../assets/proof-short.json")[..],
+        &include_bytes!("../../../assets/proof-long.json")[..],
+    ];
+
+    for data in cases {
+        let data: Value = serde_json::from_slice(data).unwrap();
+
+        let (_, mut args) = short_proof_args(EthereumProofMode::Storage);
+        let withdraw: WithdrawRequest = serde_json::from_value(data["withdraw"].clone()).unwrap();
+        let root = data["root"].as_str().unwrap().strip_prefix("0x").unwrap();
+
+        args.address = data["account"].as_str().unwrap().to_string();
+        args.root = Hash::try_from(hex::decode(root).unwrap()).unwrap();
+        args.storage = EthereumStorageLayoutBuilder::new_mapping(withdraw.id, 0x9)
+            .add_layout(&withdraw)
+            .build();
+
+        assert_eq!(args.storage, Vec::from(withdraw.clone()));
+
+        let proof = Ethereum::encode_proof(data["proof"].clone(), args).unwrap();
+        let proof = Ethereum::verify(&proof).unwrap();
+
+        let slot = EthereumMappingKey::from(withdraw.id).slot(U256::from(0x9));
+        let proven = WithdrawRequest::from_storage(slot, &proof.storage).unwrap();
+
+        assert_eq!(proven, withdraw);
+    }
+}
+
+#[test]
+fn large_balance_proof_works() {
+    let address = [0x42; 20];
+    let account = TrieAccount {
+        nonce: 3,
+        balance: U256::from(1_000_000u64) * U256::from(10u64).pow(U256::from(18)),
+        ..Default::default()
+    };
+
+    let (root, mut proofs) = account_trie(
+        &[([0x11; 20], TrieAccount::default()), (address, account)],
+        &[address],
+    );
+
+    let proof = trie_account_proof(address, &account, proofs.remove(0));
+    let proof = state_proof(root, vec![proof]);
+    let proven = Ethereum::verify(&proof).unwrap();
+
+    assert_eq!(proven.account, address);
+    assert_eq!(proven.nonce, account.nonce);
+    assert_eq!(U256::from_be_bytes(proven.balance), account.balance);
+    assert_eq!(proven.storage_root, account.storage_root.0);
+    assert_eq!(proven.code_hash, account.code_hash.0);
+}
+
+#[test]
+fn legacy_proofs_are_decoded() {
+    let (data, args) = short_proof_args(EthereumProofMode::Storage);
+
+    let mut proof = Ethereum::encode_proof(data["proof"].clone(), args).unwrap();
+    let current = EthereumStateProof::try_from_slice(&proof.proof).unwrap();
+    let current = &current.accounts[0];
+
+    let balance = U256::from_be_bytes(current.balance);
+    let legacy = EthereumStateProofV0 {
+        state_root: proof.state_root,
+        account: current.account.clone(),
+        nonce: current.nonce,
+        balance: balance.to(),
+        storage_root: current.storage_root,
+        code_hash: current.code_hash,
+        account_proof: current.account_proof.clone(),
+        storage_proofs: current.storage_proofs.clone(),
+    };
+
+    proof.proof = legacy.pack_to_vec();
+
+    let decoded = EthereumStateProof::try_from_slice(&proof.proof).unwrap();
+
+    assert_eq!(decoded.accounts.len(), 1);
+    assert_eq!(&decoded.accounts[0], current);
+
+    Ethereum::verify(&proof).unwrap();
+}
+
+#[test]
+fn account_only_proof_works() {
+    let (data, args) = short_proof_args(EthereumProofMode::Account);
+
+    let proof = Ethereum::encode_proof(data["proof"].clone(), args).unwrap();
+    let proof = Ethereum::verify(&proof).unwrap();
+
+    assert!(proof.exists);
+    assert_eq!(proof.nonce, 1);
+    assert!(proof.storage.is_empty());
+}
+
+#[test]
+fn non_existence_proof_works() {
+    let accounts = [
+        ([0x11; 20], TrieAccount::default()),
+        ([0x22; 20], TrieAccount::default()),
+        ([0x33; 20], TrieAccount::default()),
+    ];
+
+    let absent = [0x42; 20];
+    let existing = [0x22; 20];
+    let (root, mut proofs) = account_trie(&accounts, &[absent, existing]);
+
+    let proof = EthereumAccountProof {
+        account: absent.to_vec(),
+        exists: false,
+        account_proof: proofs.remove(0),
+        ..Default::default()
+    };
+
+    let proven = Ethereum::verify(&state_proof(root, vec![proof])).unwrap();
+
+    assert!(!proven.exists);
+    assert_eq!(proven.account, absent);
+    assert_eq!(proven.code_hash, KECCAK_EMPTY.0);
+
+    // an existing account cannot be proven absent
+    let proof = EthereumAccountProof {
+        account: existing.to_vec(),
+        exists: false,
+        account_proof: proofs.remove(0),
+        ..Default::default()
+    };
+
+    assert!(Ethereum::verify(&state_proof(root, vec![proof])).is_err());
+}
+
+#[test]
+fn truncated_proofs_cannot_prove_absence() {
+    let slots: Vec<_> = (1u8..=3).map(B256::repeat_byte).collect();
+    let leaves = slots
+        .iter()
+        .map(|slot| {
+            let key = Nibbles::unpack(keccak256(slot));
+
+            (key, alloy_rlp::encode(U256::from(0xdead_beefu64)))
+        })
+        .collect();
+    let (storage_root, mut storage_proofs) =
+        common::trie(leaves, &[Nibbles::unpack(keccak256(slots[1]))]);
+
+    let vault = TrieAccount {
+        nonce: 1,
+        storage_root,
+        ..Default::default()
+    };
+    let accounts = [
+        ([0x11; 20], TrieAccount::default()),
+        ([0x22; 20], vault),
+        ([0x33; 20], TrieAccount::default()),
+    ];
+    let (root, mut proofs) = account_trie(&accounts, &[[0x22; 20]]);
+    let account_proof = proofs.remove(0);
+
+    assert!(account_proof.len() > 1);
+
+    let verify = |proof: EthereumAccountProof| {
+        let expanded = state_proof(root, vec![proof]);
+        let proof = EthereumStateProof::try_from_slice(&expanded.proof).unwrap();
+        let compact = StateProof {
+            proof: EthereumCompactStateProof::from(proof).to_vec(),
+            ..expanded.clone()
+        };
+
+        [expanded, compact].map(|proof| Ethereum::verify(&proof))
+    };
+
+    // the existing account with the proof stopped at its root branch
+    let truncated = EthereumAccountProof {
+        account: vec![0x22; 20],
+        exists: false,
+        account_proof: account_proof[..1].to_vec(),
+        ..Default::default()
+    };
+
+    for result in verify(truncated) {
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<EthereumError>(),
+            Some(EthereumError::AccountProof { .. })
+        ));
+    }
+
+    // the existing slot with the proof stopped at its root branch
+    let mut storage_proof = storage_proofs.remove(0);
+
+    assert!(storage_proof.len() > 1);
+
+    storage_proof.truncate(1);
+
+    let mut proof = trie_account_proof([0x22; 20], &vault, account_proof);
+    proof.storage_proofs.push(EthereumStorageProof {
+        key: slots[1].to_vec(),
+        value: None,
+        proof: storage_proof,
+    });
+
+    for result in verify(proof) {
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<EthereumError>(),
+            Some(EthereumError::StorageProof { .. })
+        ));
+    }
+}
+
+#[test]
+fn multi_account_proof_works() {
+    let vault = TrieAccount {
+        nonce: 1,
+        balance: U256::from(10),
+        ..Default::default()
+    };
+    let oracle = TrieAccount {
+        nonce: 2,
+        ..Default::default()
+    };
+    let accounts = [
+        ([0x11; 20], vault),
+        ([0x22; 20], oracle),
+        ([0x33; 20], TrieAccount::default()),
+    ];
+
+    let (root, mut proofs) = account_trie(&accounts, &[[0x11; 20], [0x22; 20], [0x42; 20]]);
+
+    let absent = EthereumAccountProof {
+        account: vec![0x42; 20],
+        exists: false,
+        account_proof: proofs.pop().unwrap(),
+        ..Default::default()
+    };
+    let oracle = trie_account_proof([0x22; 20], &oracle, proofs.pop().unwrap());
+    let vault = trie_account_proof([0x11; 20], &vault, proofs.pop().unwrap());
+
+    let proof = state_proof(root, vec![vault, oracle, absent]);
+    let proven = Ethereum::verify_accounts(&proof).unwrap();
+
+    assert_eq!(proven.accounts.len(), 3);
+    assert_eq!(proven.accounts[0].account, [0x11; 20]);
+    assert_eq!(proven.accounts[1].account, [0x22; 20]);
+    assert!(!proven.accounts[2].exists);
+
+    let oracle = proven.get(&Address::repeat_byte(0x22)).unwrap();
+    assert_eq!(oracle.nonce, 2);
+
+    let absent = proven.get(&Address::repeat_byte(0x42)).unwrap();
+    assert!(!absent.exists);
+
+    assert!(proven.get(&Address::repeat_byte(0x33)).is_none());
+
+    // the domain circuit verifies every account, returning the first one
+    let vault = Ethereum::verify(&proof).unwrap();
+    assert_eq!(vault.account, [0x11; 20]);
+    assert_eq!(U256::from_be_bytes(vault.balance), U256::from(10));
+
+    let mut tampered = EthereumStateProof::try_from_slice(&proof.proof).unwrap();
+    tampered.accounts[1].nonce = 3;
+
+    let mut proof = proof;
+    proof.proof = tampered.to_vec();
+    assert!(Ethereum::verify(&proof).is_err());
+}
+
+#[test]
+fn bytecode_proof_works() {
+    // PUSH4 0xa9059cbb; PUSH32 with a selector in its immediate data
+    let mut code = vec![
+        0x63, 0xa9, 0x05, 0x9c, 0xbb, 0x7f, 0x63, 0x70, 0xa0, 0x82, 0x31,
+    ];
+    code.extend([0; 27]);
+
+    let token = TrieAccount {
+        code_hash: keccak256(&code),
+        ..Default::default()
+    };
+    let (root, mut proofs) = account_trie(&[([0x11; 20], token)], &[[0x11; 20], [0x42; 20]]);
+
+    let absent = EthereumAccountProof {
+        account: vec![0x42; 20],
+        exists: false,
+        account_proof: proofs.pop().unwrap(),
+        ..Default::default()
+    };
+    let token = trie_account_proof([0x11; 20], &token, proofs.pop().unwrap());
+
+    let codes = vec![
+        EthereumCodeProof {
+            account: vec![0x11; 20],
+            code: code.clone(),
+        },
+        EthereumCodeProof {
+            account: vec![0x42; 20],
+            code: vec![],
+        },
+    ];
+    let proof = EthereumStateProof {
+        state_root: root.0,
+        accounts: vec![token, absent],
+        codes,
+    };
+
+    let mut state = StateProof {
+        state_root: root.0,
+        proof: proof.to_vec(),
+        ..Default::default()
+    };
+
+    let proven = Ethereum::verify_accounts(&state).unwrap().accounts;
+
+    assert_eq!(proven[0].code.as_deref(), Some(&code[..]));
+    assert_eq!(proven[1].code.as_deref(), Some(&[][..]));
+    assert!(proven[0].has_selector([0xa9, 0x05, 0x9c, 0xbb]));
+    assert!(!proven[0].has_selector([0x70, 0xa0, 0x82, 0x31]));
+
+    state.proof = EthereumCompactStateProof::from(proof.clone()).to_vec();
+
+    let compact = Ethereum::verify_accounts(&state).unwrap().accounts;
+
+    assert_eq!(compact[0].code, proven[0].code);
+    assert_eq!(compact[1].code, proven[1].code);
+
+    let mut tampered = proof.clone();
+    tampered.codes[0].code.push(0x00);
+    state.proof = tampered.to_vec();
+
+    let err = Ethereum::verify_accounts(&state).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::CodeProof { account, .. }) if account == &[0x11; 20]
+    ));
+
+    let mut unknown = proof;
+    unknown.codes[0].account = vec![0x33; 20];
+    state.proof = unknown.to_vec();
+
+    assert!(Ethereum::verify_accounts(&state).is_err());
+}
+
+#[test]
+fn encode_bytecode_proofs_are_checked() {
+    let (data, mut args) = short_proof_args(EthereumProofMode::Storage);
+
+    args.code = true;
+
+    let proofs = vec![data["proof"].clone()];
+    let err = Ethereum::encode_proofs(proofs.clone(), args.clone()).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::Config { .. })
+    ));
+
+    let err = Ethereum::encode_proofs_with_codes(proofs, vec![json!("0x6001")], args).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::CodeProof { .. })
+    ));
+}
+
+#[test]
+fn encode_multi_account_proofs_works() {
+    let (data, mut args) = short_proof_args(EthereumProofMode::Storage);
+
+    args.accounts.push(EthereumAccountProofArgs {
+        address: args.address.clone(),
+        storage: vec![],
+        mode: EthereumProofMode::Account,
+        code: false,
+    });
+
+    let proofs = vec![data["proof"].clone(), data["proof"].clone()];
+    let proof = Ethereum::encode_proofs(proofs, args.clone()).unwrap();
+    let proven = Ethereum::verify_accounts(&proof).unwrap().accounts;
+
+    assert_eq!(proven.len(), 2);
+    assert_eq!(proven[0].storage.len(), args.storage.len());
+    assert!(proven[1].storage.is_empty());
+    assert_eq!(proven[0].account, proven[1].account);
+
+    assert!(Ethereum::encode_proof(data["proof"].clone(), args).is_err());
+}
+
+#[test]
+fn fetched_storage_values_works() {
+    let (data, mut args) = short_proof_args(EthereumProofMode::Storage);
+    let expected = Ethereum::encode_proof(data["proof"].clone(), args.clone()).unwrap();
+
+    for arg in args.storage.iter_mut() {
+        arg.value = None;
+    }
+
+    args.values = EthereumStorageValues::Fetched;
+
+    let proof = Ethereum::encode_proof(data["proof"].clone(), args).unwrap();
+    let proven = Ethereum::verify(&proof).unwrap();
+
+    assert_eq!(proven.storage, Ethereum::verify(&expected).unwrap().storage);
+}
+
+#[test]
+fn checked_storage_values_works() {
+    let (data, mut args) = short_proof_args(EthereumProofMode::Storage);
+
+    args.values = EthereumStorageValues::Checked;
+
+    let proof = Ethereum::encode_proof(data["proof"].clone(), args.clone()).unwrap();
+
+    assert!(Ethereum::verify(&proof).is_ok());
+
+    args.storage[1].value = Some(alloy_rlp::encode(U256::from(42)));
+
+    assert!(Ethereum::encode_proof(data["proof"].clone(), args).is_err());
+}
+
+#[test]
+fn storage_responses_are_matched_by_key() {
+    let (mut data, args) = short_proof_args(EthereumProofMode::Storage);
+    let expected = Ethereum::encode_proof(data["proof"].clone(), args.clone()).unwrap();
+
+    data["proof"]["storageProof"]
+        .as_array_mut()
+        .unwrap()
+        .reverse();
+
+    let proof = Ethereum::encode_proof(data["proof"].clone(), args.clone()).unwrap();
+
+    assert_eq!(
+        Ethereum::verify(&proof).unwrap().storage,
+        Ethereum::verify(&expected).unwrap().storage
+    );
+
+    let mut missing = data.clone();
+    let dropped = missing["proof"]["storageProof"]
+        .as_array_mut()
+        .unwrap()
+        .remove(0);
+    let dropped =
+        hex::decode(dropped["key"].as_str().unwrap().strip_prefix("0x").unwrap()).unwrap();
+
+    let err = Ethereum::encode_proof(missing["proof"].clone(), args.clone()).unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<EthereumError>(),
+        Some(&EthereumError::MissingSlot { key: dropped })
+    );
+
+    let mut unexpected = args.clone();
+    let extra = unexpected.storage.remove(0).key;
+    let err = Ethereum::encode_proof(data["proof"].clone(), unexpected).unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<EthereumError>(),
+        Some(&EthereumError::UnexpectedSlot { key: extra })
+    );
+}
+
+#[test]
+fn mismatched_responses_are_rejected() {
+    let (data, args) = short_proof_args(EthereumProofMode::Storage);
+
+    let mut other = args.clone();
+    other.address = "0x0000000000000000000000000000000000000001".into();
+
+    let err = Ethereum::encode_proof(data["proof"].clone(), other).unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::AccountMismatch { .. })
+    ));
+
+    let mut tampered = args.clone();
+    tampered.storage[1].value = Some(alloy_rlp::encode(U256::from(42)));
+    let key = tampered.storage[1].key.clone();
+
+    let err = Ethereum::encode_proof(data["proof"].clone(), tampered).unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::StorageProof { key: k, .. }) if *k == key
+    ));
+
+    let mut root = args;
+    root.root = [0; 32];
+
+    let err = Ethereum::encode_proof(data["proof"].clone(), root).unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::AccountProof { .. })
+    ));
+}
+
+#[test]
+fn memory_provider_state_proof_works() {
+    let (data, args) = short_proof_args(EthereumProofMode::Storage);
+    let expected = Ethereum::encode_proof(data["proof"].clone(), args.clone()).unwrap();
+
+    let keys: Vec<_> = args
+        .storage
+        .iter()
+        .map(|s| U256::from_be_slice(&s.key))
+        .collect();
+    let params = json!([args.address, keys, U256::from(args.block)]);
+    let provider = EthereumMemoryProvider::default().with_response(
+        "eth_getProof",
+        params,
+        data["proof"].clone(),
+    );
+
+    let args = serde_json::to_value(args).unwrap();
+    let proof = Ethereum::state_proof_with(&provider, args.clone()).unwrap();
+
+    assert_eq!(proof, expected);
+
+    let err = Ethereum::state_proof_with(&EthereumMemoryProvider::default(), args).unwrap_err();
+
+    assert!(err.to_string().contains("eth_getProof"));
+}
+
+#[test]
+fn provider_config_is_read_from_args() {
+    let provider = EthereumProviderConfig::Http {
+        url: "http://localhost:8545".into(),
+    };
+
+    let args =
+        EthereumStateProofArgsBuilder::new("0x0000000000000000000000000000000000000001".into())
+            .with_block(1, [0; 32])
+            .with_provider(provider.clone())
+            .build()
+            .unwrap();
+
+    assert_eq!(EthereumProviderConfig::from_args(&args).unwrap(), provider);
+
+    let args: EthereumStateProofArgs = serde_json::from_value(args).unwrap();
+    let args = serde_json::to_value(args).unwrap();
+
+    assert_eq!(
+        EthereumProviderConfig::from_args(&args).unwrap(),
+        EthereumProviderConfig::default()
+    );
+}
+
+#[test]
+fn proofs_are_bound_to_their_domain() {
+    let (data, args) = short_proof_args(EthereumProofMode::Storage);
+
+    let mut proof = Ethereum::encode_proof(data["proof"].clone(), args.clone()).unwrap();
+
+    assert_eq!(proof.domain, EthereumNetwork::Mainnet.domain());
+    assert!(Ethereum::verify_network(&proof, EthereumNetwork::Mainnet).is_ok());
+
+    proof.domain = [0x01; 32];
+    assert!(Ethereum::verify_network(&proof, EthereumNetwork::Mainnet).is_err());
+
+    assert_eq!(EthereumNetwork::Mainnet.id(), Ethereum::ID);
+
+    for network in EthereumNetwork::ALL {
+        assert_eq!(network.to_string().parse(), Ok(network));
+    }
+
+    // the testnets have no light client
+    for network in ["sepolia", "holesky"] {
+        assert!(network.parse::<EthereumNetwork>().is_err());
+        assert!(serde_json::from_value::<EthereumNetwork>(json!(network)).is_err());
+    }
+}
+
+#[test]
+fn block_range_is_enforced() {
+    let (data, mut args) = short_proof_args(EthereumProofMode::Storage);
+
+    args.block = 100;
+
+    let proof = Ethereum::encode_proof(data["proof"].clone(), args).unwrap();
+
+    for range in [
+        EthereumBlockRange::default(),
+        EthereumBlockRange::exact(100),
+        EthereumBlockRange::default().with_min(90).with_max(100),
+    ] {
+        assert!(Ethereum::verify_in_range(&proof, &range).is_ok());
+        assert!(Ethereum::verify_accounts_in_range(&proof, &range).is_ok());
+    }
+
+    for range in [
+        EthereumBlockRange::exact(101),
+        EthereumBlockRange::default().with_min(101),
+        EthereumBlockRange::default().with_max(99),
+        EthereumBlockRange::default().with_min(95).with_max(99),
+    ] {
+        let err = Ethereum::verify_in_range(&proof, &range).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<EthereumError>(),
+            Some(&EthereumError::BlockOutOfRange { number: 100, range })
+        );
+
+        let err = Ethereum::verify_accounts_in_range(&proof, &range).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<EthereumError>(),
+            Some(&EthereumError::BlockOutOfRange { number: 100, range })
+        );
+    }
+}
+
+#[test]
+fn errors_are_typed() {
+    let (data, args) = short_proof_args(EthereumProofMode::Storage);
+    let proof = Ethereum::encode_proof(data["proof"].clone(), args).unwrap();
+
+    let mut other = proof.clone();
+    other.domain = [0x01; 32];
+
+    let err = Ethereum::verify_network(&other, EthereumNetwork::Mainnet).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<EthereumError>(),
+        Some(&EthereumError::DomainMismatch {
+            network: EthereumNetwork::Mainnet
+        })
+    );
+
+    let mut garbage = proof.clone();
+    garbage.proof = vec![0xff; 4];
+
+    let err = Ethereum::verify(&garbage).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::Decode { .. })
+    ));
+
+    let proven = Ethereum::verify(&proof).unwrap();
+    let err = proven.storage_value(U256::MAX).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<EthereumError>(),
+        Some(&EthereumError::UnprovenSlot {
+            key: U256::MAX.to_be_bytes::<32>().to_vec()
+        })
+    );
+
+    let err =
+        Ethereum::state_proof_with(&EthereumMemoryProvider::default(), json!({})).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::Config { .. })
+    ));
+}
+
+#[test]
+fn compact_proofs_works() {
+    let data = include_bytes!("../../../assets/proof-long.json");
+    let data: Value = serde_json::from_slice(data).unwrap();
+
+    let (_, mut args) = short_proof_args(EthereumProofMode::Storage);
+    let withdraw: WithdrawRequest = serde_json::from_value(data["withdraw"].clone()).unwrap();
+    let root = data["root"].as_str().unwrap().strip_prefix("0x").unwrap();
+
+    args.address = data["account"].as_str().unwrap().to_string();
+    args.root = Hash::try_from(hex::decode(root).unwrap()).unwrap();
+    args.storage = Vec::from(withdraw);
+
+    let expanded = Ethereum::encode_proof(data["proof"].clone(), args.clone()).unwrap();
+
+    args.compact = true;
+
+    let compact = Ethereum::encode_proof(data["proof"].clone(), args).unwrap();
+
+    assert!(EthereumCompactStateProof::is_compact(&compact.proof));
+    assert!(compact.proof.len() < expanded.proof.len());
+
+    let decoded = EthereumCompactStateProof::try_from_slice(&compact.proof).unwrap();
+    let paths: usize = decoded.accounts[0]
+        .storage_proofs
+        .iter()
+        .map(|p| p.proof.len())
+        .sum::<usize>()
+        + decoded.accounts[0].account_proof.len();
+
+    assert!(decoded.nodes.len() < paths);
+
+    let expected = Ethereum::verify(&expanded).unwrap();
+    let proven = Ethereum::verify(&compact).unwrap();
+
+    assert_eq!(proven.storage, expected.storage);
+    assert_eq!(proven.code_hash, expected.code_hash);
+
+    // tampered values must be rejected
+    let mut tampered = decoded.clone();
+    tampered.accounts[0].storage_proofs[1].value = Some(vec![0x01]);
+
+    let mut proof = compact.clone();
+    proof.proof = tampered.to_vec();
+    assert!(Ethereum::verify(&proof).is_err());
+
+    // out of bounds node references must be rejected
+    let mut tampered = decoded;
+    tampered.accounts[0].account_proof[0] = u32::MAX;
+
+    let mut proof = compact;
+    proof.proof = tampered.to_vec();
+    assert!(Ethereum::verify(&proof).is_err());
+}
+
+#[test]
+fn compact_non_existence_proof_works() {
+    let accounts = [
+        ([0x11; 20], TrieAccount::default()),
+        ([0x22; 20], TrieAccount::default()),
+    ];
+
+    let (root, mut proofs) = account_trie(&accounts, &[[0x42; 20], [0x11; 20]]);
+
+    let existing = trie_account_proof([0x11; 20], &TrieAccount::default(), proofs.pop().unwrap());
+    let absent = EthereumAccountProof {
+        account: vec![0x42; 20],
+        exists: false,
+        account_proof: proofs.pop().unwrap(),
+        ..Default::default()
+    };
+
+    let proof = EthereumStateProof {
+        state_root: root.0,
+        accounts: vec![absent, existing],
+        codes: vec![],
+    };
+
+    let proof = StateProof {
+        state_root: root.0,
+        proof: EthereumCompactStateProof::from(proof).to_vec(),
+        ..Default::default()
+    };
+
+    let proven = Ethereum::verify_accounts(&proof).unwrap().accounts;
+
+    assert!(!proven[0].exists);
+    assert!(proven[1].exists);
+}
+
+/// Loads the short proof asset with its proof arguments.
+fn short_proof_args(mode: EthereumProofMode) -> (Value, EthereumStateProofArgs) {
+    let data = include_bytes!("../../../assets/proof-short.json");
+    let data: Value = serde_json::from_slice(data).unwrap();
+
+    let address = data["account"].as_str().unwrap().to_string();
+    let root = data["root"].as_str().unwrap().strip_prefix("0x").unwrap();
+    let root = Hash::try_from(hex::decode(root).unwrap()).unwrap();
+
+    let withdraw: WithdrawRequest = serde_json::from_value(data["withdraw"].clone()).unwrap();
+    let args = EthereumStateProofArgs {
+        address,
+        block: 0,
+        root,
+        storage: Vec::from(withdraw),
+        payload: vec![],
+        mode,
+        accounts: vec![],
+        compact: false,
+        values: EthereumStorageValues::Provided,
+        network: EthereumNetwork::Mainnet,
+        code: false,
+        fetched: vec![],
+    };
+
+    (data, args)
+}
+
+/// Wraps account proofs into a state proof for the given root.
+fn state_proof(root: B256, accounts: Vec<EthereumAccountProof>) -> StateProof {
+    let proof = EthereumStateProof {
+        state_root: root.0,
+        accounts,
+        codes: vec![],
+    };
+
+    StateProof {
+        state_root: root.0,
+        proof: proof.to_vec(),
+        ..Default::default()
+    }
+}
+
+/// Creates the proof of an existing account without storage.
+fn trie_account_proof(
+    address: [u8; 20],
+    account: &TrieAccount,
+    account_proof: Vec<Vec<u8>>,
+) -> EthereumAccountProof {
+    EthereumAccountProof {
+        account: address.to_vec(),
+        exists: true,
+        nonce: account.nonce,
+        balance: account.balance.to_be_bytes(),
+        storage_root: account.storage_root.0,
+        code_hash: account.code_hash.0,
+        account_proof,
+        storage_proofs: vec![],
+    }
+}
+
+/// Builds an account trie, returning its root and the proofs for each of the `targets`.
+fn account_trie(
+    accounts: &[([u8; 20], TrieAccount)],
+    targets: &[[u8; 20]],
+) -> (B256, Vec<Vec<Vec<u8>>>) {
+    let leaves = accounts
+        .iter()
+        .map(|(address, account)| {
+            let key = Nibbles::unpack(keccak256(address));
+
+            (key, alloy_rlp::encode(account))
+        })
+        .collect();
+
+    let targets: Vec<_> = targets
+        .iter()
+        .map(|t| Nibbles::unpack(keccak256(t)))
+        .collect();
+
+    common::trie(leaves, &targets)
+}
+
 alloy_sol_types::sol! {
-    #![sol(extra_derives(Debug, Serialize, Deserialize, RlpEncodable, RlpDecodable))]
+    #![sol(extra_derives(
+        Debug,
+        PartialEq,
+        Serialize,
+        Deserialize,
+        RlpEncodable,
+        RlpDecodable,
+        StorageLayout
+    ))]
     struct WithdrawRequest {
         uint64 id;
         address owner;
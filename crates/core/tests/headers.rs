@@ -0,0 +1,345 @@
+use alloy_consensus::Header;
+use alloy_primitives::{keccak256, Address, B256, U256};
+use alloy_trie::{Nibbles, TrieAccount};
+use serde_json::{json, Value};
+use valence_coprocessor::StateProof;
+use valence_coprocessor_ethereum::{
+    controller::EthereumStorageLayoutBuilder, Ethereum, EthereumAccountProofArgs,
+    EthereumBlockProof, EthereumBlockProofArgs, EthereumBlockRange, EthereumError, EthereumNetwork,
+    EthereumProofMode, EthereumStorageProofArg,
+};
+
+mod common;
+
+const TARGET: u64 = 22_500_000;
+const VAULT: [u8; 20] = [0xaa; 20];
+
+#[test]
+fn block_proof_works() {
+    let (proof, header) = block_proof();
+    let proven = Ethereum::verify_block(&proof).unwrap();
+
+    assert_eq!(proven.number, TARGET);
+    assert_eq!(proven.hash, header.hash_slow().0);
+    assert_eq!(proven.parent_hash, header.parent_hash.0);
+    assert_eq!(proven.state_root, header.state_root.0);
+    assert_eq!(proven.receipts_root, header.receipts_root.0);
+    assert_eq!(proven.transactions_root, header.transactions_root.0);
+    assert_eq!(proven.timestamp, 1_750_000_000);
+    assert_eq!(proven.base_fee_per_gas, Some(7));
+    assert_eq!(proven.payload, b"block");
+
+    assert_eq!(proven.accounts.len(), 1);
+    assert_eq!(proven.accounts[0].account, VAULT.to_vec());
+    assert_eq!(proven.accounts[0].nonce, 3);
+    assert_eq!(
+        proven.accounts[0].storage[0].value,
+        Some(alloy_rlp::encode(U256::from(42)))
+    );
+}
+
+#[test]
+fn block_proof_rejects_tampered_headers() {
+    let (mut proof, _) = block_proof();
+    let mut block = EthereumBlockProof::try_from_slice(&proof.proof).unwrap();
+
+    let mut header: Header = alloy_rlp::decode_exact(&block.block.header).unwrap();
+    header.timestamp += 12;
+    block.block.header = alloy_rlp::encode(&header);
+    proof.proof = block.to_vec();
+
+    assert!(Ethereum::verify_block(&proof).is_err());
+}
+
+#[test]
+fn block_proof_rejects_accounts_of_other_states() {
+    let (mut proof, _) = block_proof();
+    let mut block = EthereumBlockProof::try_from_slice(&proof.proof).unwrap();
+
+    block.accounts[0].nonce += 1;
+    proof.proof = block.to_vec();
+
+    assert!(Ethereum::verify_block(&proof).is_err());
+}
+
+#[test]
+fn historical_block_proof_works() {
+    let proof = historical_block_proof(3);
+    let proven = Ethereum::verify_block(&proof).unwrap();
+
+    assert_eq!(proven.number, TARGET);
+    assert_eq!(proven.accounts[0].account, VAULT.to_vec());
+    assert_eq!(proven.accounts[0].nonce, 3);
+
+    let block = EthereumBlockProof::try_from_slice(&proof.proof).unwrap();
+
+    assert_eq!(block.ancestors.len(), 3);
+}
+
+#[test]
+fn historical_block_proof_rejects_broken_chains() {
+    let mut proof = historical_block_proof(3);
+    let mut block = EthereumBlockProof::try_from_slice(&proof.proof).unwrap();
+
+    block.ancestors.remove(1);
+    proof.proof = block.to_vec();
+
+    assert!(Ethereum::verify_block(&proof).is_err());
+
+    let mut proof = historical_block_proof(3);
+    let mut block = EthereumBlockProof::try_from_slice(&proof.proof).unwrap();
+
+    let mut header: Header = alloy_rlp::decode_exact(&block.ancestors[2]).unwrap();
+    header.state_root = B256::repeat_byte(0x02);
+    block.ancestors[2] = alloy_rlp::encode(&header);
+    proof.proof = block.to_vec();
+
+    assert!(Ethereum::verify_block(&proof).is_err());
+}
+
+#[test]
+fn historical_block_proof_rejects_overflowing_numbers() {
+    let (state_root, storage, account) = vault();
+
+    let parent = Header {
+        state_root,
+        number: u64::MAX,
+        ..Default::default()
+    };
+    let anchor = Header {
+        parent_hash: parent.hash_slow(),
+        state_root: B256::repeat_byte(0x01),
+        number: TARGET,
+        ..Default::default()
+    };
+
+    let (root, block, history) = common::history(&anchor);
+    let mut ancestor = serde_json::to_value(&parent).unwrap();
+    ancestor["hash"] = json!(parent.hash_slow());
+
+    let mut args = block_args(TARGET + 64, root, storage);
+    args.target = u64::MAX;
+
+    let proof = Ethereum::encode_historical_block_proof(
+        block,
+        vec![ancestor],
+        history,
+        vec![account],
+        args,
+    )
+    .unwrap();
+
+    let err = Ethereum::verify_block(&proof).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::BlockHeader {
+            number: u64::MAX,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn historical_block_proof_range_checks_the_proven_block() {
+    let proof = historical_block_proof(3);
+
+    let range = EthereumBlockRange::exact(TARGET);
+    assert!(Ethereum::verify_block_in_range(&proof, &range).is_ok());
+
+    // the anchored block is in range, but not the block reached through its ancestors
+    let range = EthereumBlockRange::exact(TARGET + 3);
+    let err = Ethereum::verify_block_in_range(&proof, &range).unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<EthereumError>(),
+        Some(&EthereumError::BlockOutOfRange {
+            number: TARGET,
+            range
+        })
+    );
+}
+
+#[test]
+fn validated_header_is_proven_against_its_hash() {
+    let header = Header {
+        parent_hash: B256::repeat_byte(0x01),
+        state_root: B256::repeat_byte(0x02),
+        receipts_root: B256::repeat_byte(0x03),
+        number: TARGET,
+        timestamp: 1_750_000_000,
+        base_fee_per_gas: Some(7),
+        ..Default::default()
+    };
+
+    let mut block = serde_json::to_value(&header).unwrap();
+    block["hash"] = json!(header.hash_slow());
+
+    let encoded = Ethereum::encode_validated_header(block.clone()).unwrap();
+    let hash = header.hash_slow().0;
+    let proof = StateProof {
+        state_root: header.state_root.0,
+        number: TARGET,
+        payload: b"header".to_vec(),
+        ..Default::default()
+    };
+
+    let proven = Ethereum::verify_validated_header(&proof, &encoded, &hash).unwrap();
+
+    assert_eq!(proven.number, TARGET);
+    assert_eq!(proven.hash, hash);
+    assert_eq!(proven.receipts_root, header.receipts_root.0);
+    assert_eq!(proven.timestamp, 1_750_000_000);
+    assert_eq!(proven.base_fee_per_gas, Some(7));
+    assert_eq!(proven.payload, b"header");
+
+    // the header must hash to the validated block hash
+    let err = Ethereum::verify_validated_header(&proof, &encoded, &[0x01; 32]).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::BlockHeader { number: TARGET, .. })
+    ));
+
+    // and be the one of the proven state
+    let mut other = proof.clone();
+    other.state_root = [0x04; 32];
+    assert!(Ethereum::verify_validated_header(&other, &encoded, &hash).is_err());
+
+    let mut other = proof;
+    other.number += 1;
+    assert!(Ethereum::verify_validated_header(&other, &encoded, &hash).is_err());
+
+    // the controller rejects responses not matching their hash
+    block["hash"] = json!(B256::repeat_byte(0x05));
+    assert!(Ethereum::encode_validated_header(block).is_err());
+}
+
+/// Encodes a block proof of the target block with the vault account.
+fn block_proof() -> (StateProof, Header) {
+    let (state_root, storage, account) = vault();
+
+    let header = Header {
+        parent_hash: B256::repeat_byte(0x01),
+        state_root,
+        transactions_root: B256::repeat_byte(0x02),
+        receipts_root: B256::repeat_byte(0x03),
+        number: TARGET,
+        timestamp: 1_750_000_000,
+        base_fee_per_gas: Some(7),
+        ..Default::default()
+    };
+
+    let (root, block, history) = common::history(&header);
+    let args = block_args(TARGET + 64, root, storage);
+    let proof = Ethereum::encode_block_proof(block, history, vec![account], args).unwrap();
+
+    (proof, header)
+}
+
+/// Encodes a block proof of the target block, anchored at its `depth`-th descendant.
+fn historical_block_proof(depth: u64) -> StateProof {
+    let (state_root, storage, account) = vault();
+
+    let mut headers = vec![Header {
+        state_root,
+        number: TARGET,
+        ..Default::default()
+    }];
+
+    for _ in 0..depth {
+        let parent = headers.last().unwrap();
+
+        headers.push(Header {
+            parent_hash: parent.hash_slow(),
+            state_root: B256::repeat_byte(0x01),
+            number: parent.number + 1,
+            ..Default::default()
+        });
+    }
+
+    let anchor = headers.pop().unwrap();
+    let (root, block, history) = common::history(&anchor);
+
+    let ancestors = headers
+        .iter()
+        .rev()
+        .map(|header| {
+            let mut block = serde_json::to_value(header).unwrap();
+
+            block["hash"] = json!(header.hash_slow());
+            block
+        })
+        .collect();
+
+    let args = block_args(
+        anchor.number + Ethereum::HISTORY_SERVE_WINDOW,
+        root,
+        storage,
+    );
+
+    Ethereum::encode_historical_block_proof(block, ancestors, history, vec![account], args).unwrap()
+}
+
+fn block_args(
+    block: u64,
+    root: B256,
+    storage: Vec<EthereumStorageProofArg>,
+) -> EthereumBlockProofArgs {
+    EthereumBlockProofArgs {
+        block,
+        root: root.0,
+        target: TARGET,
+        accounts: vec![EthereumAccountProofArgs {
+            address: Address::from(VAULT).to_string(),
+            storage,
+            mode: EthereumProofMode::Storage,
+            code: false,
+        }],
+        payload: b"block".to_vec(),
+        network: EthereumNetwork::Mainnet,
+    }
+}
+
+/// Creates a state with the vault account, returning its root with the vault storage layout and
+/// the `eth_getProof` response of the vault.
+fn vault() -> (B256, Vec<EthereumStorageProofArg>, Value) {
+    let storage = EthereumStorageLayoutBuilder::new(0)
+        .add_value(U256::from(42).to_be_bytes_trimmed_vec())
+        .build();
+
+    let slot = B256::from_slice(&storage[0].key);
+    let slot_key = Nibbles::unpack(keccak256(slot));
+    let (storage_root, mut storage_proofs) = common::trie(
+        vec![(slot_key.clone(), alloy_rlp::encode(U256::from(42)))],
+        &[slot_key],
+    );
+
+    let vault = TrieAccount {
+        nonce: 3,
+        balance: U256::from(1_000),
+        storage_root,
+        code_hash: keccak256(b"vault"),
+    };
+
+    let vault_key = Nibbles::unpack(keccak256(VAULT));
+    let (state_root, mut account_proofs) = common::trie(
+        vec![
+            (vault_key.clone(), alloy_rlp::encode(vault)),
+            (
+                Nibbles::unpack(keccak256([0x11; 20])),
+                alloy_rlp::encode(TrieAccount::default()),
+            ),
+        ],
+        &[vault_key],
+    );
+
+    let storage_proof = json!([{
+        "key": slot,
+        "value": U256::from(42),
+        "proof": common::bytes(storage_proofs.pop().unwrap()),
+    }]);
+
+    let account =
+        common::account_response(VAULT, &vault, account_proofs.pop().unwrap(), storage_proof);
+
+    (state_root, storage, account)
+}
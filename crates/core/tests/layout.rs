@@ -0,0 +1,601 @@
+use alloy_primitives::{
+    aliases::{I24, U64},
+    b256, keccak256, Address, B256, I256, U256,
+};
+use alloy_sol_types::SolValue as _;
+use alloy_trie::{Nibbles, TrieAccount};
+use serde_json::{json, Value};
+use valence_coprocessor::DomainCircuit as _;
+use valence_coprocessor_ethereum::{
+    controller::{EthereumStateProofArgsBuilder, EthereumStorageLayoutBuilder},
+    Ethereum, EthereumArrayLayout, EthereumError, EthereumMappingKey, EthereumPackedLayout,
+    EthereumProvenAccount, EthereumProxySlot, EthereumStateProofArgs,
+};
+
+mod common;
+
+#[test]
+fn mapping_keys_are_encoded() {
+    let slot = U256::from(3);
+    let owner = Address::repeat_byte(0x11);
+
+    let cases = [
+        (EthereumMappingKey::from(owner), (owner, slot).abi_encode()),
+        (
+            EthereumMappingKey::from(B256::repeat_byte(0x22)),
+            (B256::repeat_byte(0x22), slot).abi_encode(),
+        ),
+        (
+            EthereumMappingKey::from(42u64),
+            (U256::from(42), slot).abi_encode(),
+        ),
+        (
+            EthereumMappingKey::from(-42i64),
+            (I256::try_from(-42).unwrap(), slot).abi_encode(),
+        ),
+        (EthereumMappingKey::from(true), (true, slot).abi_encode()),
+        (
+            EthereumMappingKey::from("withdrawals"),
+            ("withdrawals".to_string(), slot).abi_encode_packed(),
+        ),
+        (
+            EthereumMappingKey::from(vec![0xde, 0xad]),
+            [&[0xde, 0xad][..], &slot.to_be_bytes::<32>()].concat(),
+        ),
+    ];
+
+    for (key, preimage) in cases {
+        assert_eq!(
+            key.slot(slot),
+            U256::from_be_bytes(keccak256(preimage).0),
+            "{key:?}"
+        );
+    }
+}
+
+#[test]
+fn nested_mappings_are_entered() {
+    let owner = Address::repeat_byte(0x11);
+    let spender = Address::repeat_byte(0x22);
+
+    let storage = EthereumStorageLayoutBuilder::new(1)
+        .enter_mapping(owner)
+        .enter_mapping(spender)
+        .add_value([0x01])
+        .build();
+
+    let inner = keccak256((owner, U256::from(1)).abi_encode());
+    let slot = keccak256((spender, inner).abi_encode());
+
+    assert_eq!(storage[0].key, slot.to_vec());
+}
+
+#[test]
+fn typed_mappings_match_legacy_mappings() {
+    assert_eq!(
+        EthereumStorageLayoutBuilder::new_mapping(7, 9)
+            .add_value([0x01])
+            .build(),
+        EthereumStorageLayoutBuilder::new_mapping_with_key(U256::from(7), 9)
+            .add_value([0x01])
+            .build()
+    );
+}
+
+#[test]
+fn dynamic_arrays_are_packed() {
+    let items: Vec<_> = (1..=5u64).map(|i| i.to_be_bytes()).collect();
+    let storage = EthereumStorageLayoutBuilder::new(2)
+        .add_dynamic_array(8, &items)
+        .add_value([0x01])
+        .build();
+
+    let data = U256::from_be_bytes(keccak256(U256::from(2).to_be_bytes::<32>()).0);
+
+    assert_eq!(storage.len(), 4);
+
+    assert_eq!(storage[0].key, U256::from(2).to_be_bytes::<32>().to_vec());
+    assert_eq!(storage[0].value, Some(alloy_rlp::encode(U256::from(5))));
+
+    let word = U256::from(1) | U256::from(2) << 64 | U256::from(3) << 128 | U256::from(4) << 192;
+
+    assert_eq!(storage[1].key, data.to_be_bytes::<32>().to_vec());
+    assert_eq!(storage[1].value, Some(alloy_rlp::encode(word)));
+
+    assert_eq!(
+        storage[2].key,
+        (data + U256::ONE).to_be_bytes::<32>().to_vec()
+    );
+    assert_eq!(storage[2].value, Some(alloy_rlp::encode(U256::from(5))));
+
+    // the next variable follows the length slot
+    assert_eq!(storage[3].key, U256::from(3).to_be_bytes::<32>().to_vec());
+
+    let layout = EthereumArrayLayout::dynamic(U256::from(2), 8);
+
+    for (i, (item, arg)) in items.iter().zip([1, 1, 1, 1, 2]).enumerate() {
+        let position = layout.position(i as u64);
+        let word = Ethereum::decode_slot_value(storage[arg].value.as_deref()).unwrap();
+
+        assert_eq!(
+            position.slot().to_be_bytes::<32>().to_vec(),
+            storage[arg].key
+        );
+        assert_eq!(position.read(&word), item);
+    }
+}
+
+#[test]
+fn fixed_arrays_advance_the_base_slot() {
+    let owners = [Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+    let storage = EthereumStorageLayoutBuilder::new(4)
+        .add_fixed_array(20, owners)
+        .add_value([0x01])
+        .build();
+
+    assert_eq!(storage.len(), 3);
+    assert_eq!(storage[0].key, U256::from(4).to_be_bytes::<32>().to_vec());
+    assert_eq!(
+        storage[0].value,
+        Some(alloy_rlp::encode(U256::from_be_slice(owners[0].as_slice())))
+    );
+    assert_eq!(storage[1].key, U256::from(5).to_be_bytes::<32>().to_vec());
+    assert_eq!(storage[2].key, U256::from(6).to_be_bytes::<32>().to_vec());
+}
+
+#[test]
+fn arrays_of_structs_are_entered() {
+    // struct of three slots, stored in a dynamic array at slot 7
+    let storage = EthereumStorageLayoutBuilder::new(7)
+        .enter_dynamic_array(96, 2)
+        .add_value([0x01])
+        .add_value([0x02])
+        .build();
+
+    let data = U256::from_be_bytes(keccak256(U256::from(7).to_be_bytes::<32>()).0);
+
+    assert_eq!(
+        storage[0].key,
+        (data + U256::from(6)).to_be_bytes::<32>().to_vec()
+    );
+    assert_eq!(
+        storage[1].key,
+        (data + U256::from(7)).to_be_bytes::<32>().to_vec()
+    );
+
+    let storage = EthereumStorageLayoutBuilder::new(7)
+        .enter_fixed_array(64, 3)
+        .add_value([0x01])
+        .build();
+
+    assert_eq!(storage[0].key, U256::from(13).to_be_bytes::<32>().to_vec());
+}
+
+#[test]
+fn zero_slots_are_proven_absent() {
+    let storage = EthereumStorageLayoutBuilder::new(0)
+        .add_dynamic_array(32, Vec::<[u8; 32]>::new())
+        .add_fixed_array(16, [[0u8; 16], [0u8; 16]])
+        .build();
+
+    assert_eq!(storage.len(), 2);
+    assert_eq!(storage[0].value, None);
+    assert_eq!(storage[1].value, None);
+    assert_eq!(Ethereum::decode_slot_value(None).unwrap(), [0; 32]);
+}
+
+#[test]
+fn packed_values_follow_declaration_order() {
+    let owner = Address::repeat_byte(0x11);
+    let id = 42u64.to_be_bytes();
+    let amount = 7u128.to_be_bytes();
+
+    let storage = EthereumStorageLayoutBuilder::new(9)
+        .add_packed_values([
+            (8, &id[..]),
+            (20, owner.as_slice()),
+            (16, &amount[..]),
+            (1, &[0x01][..]),
+        ])
+        .add_value([0x05])
+        .build();
+
+    assert_eq!(storage.len(), 3);
+
+    let word = U256::from(42) | U256::from_be_slice(owner.as_slice()) << 64;
+
+    assert_eq!(storage[0].key, U256::from(9).to_be_bytes::<32>().to_vec());
+    assert_eq!(storage[0].value, Some(alloy_rlp::encode(word)));
+
+    let word = U256::from(7) | U256::from(1) << 128;
+
+    assert_eq!(storage[1].key, U256::from(10).to_be_bytes::<32>().to_vec());
+    assert_eq!(storage[1].value, Some(alloy_rlp::encode(word)));
+
+    assert_eq!(storage[2].key, U256::from(11).to_be_bytes::<32>().to_vec());
+
+    let fields = EthereumPackedLayout::new([8, 20, 16, 1])
+        .extract(U256::from(9), &storage)
+        .unwrap();
+
+    assert_eq!(
+        fields,
+        vec![id.to_vec(), owner.to_vec(), amount.to_vec(), vec![0x01]]
+    );
+}
+
+#[test]
+fn packed_values_match_combined_values() {
+    let owner = Address::repeat_byte(0x11);
+    let id = 42u64.to_be_bytes();
+
+    assert_eq!(
+        EthereumStorageLayoutBuilder::new(0)
+            .add_combined_values([owner.as_slice(), &id[..]])
+            .build(),
+        EthereumStorageLayoutBuilder::new(0)
+            .add_packed_values([(8, &id[..]), (20, owner.as_slice())])
+            .build()
+    );
+}
+
+#[test]
+fn packed_layout_extraction_requires_proven_slots() {
+    let storage = EthereumStorageLayoutBuilder::new(0)
+        .add_packed_values([(16, [0x01]), (16, [0x02]), (32, [0x03])])
+        .build();
+
+    let layout = EthereumPackedLayout::new([16, 16, 32]);
+
+    assert_eq!(layout.slots(), 2);
+    assert!(layout.extract(U256::ZERO, &storage).is_ok());
+    assert!(layout.extract(U256::ONE, &storage).is_err());
+}
+
+#[test]
+fn proven_values_are_decoded() {
+    let owner = Address::repeat_byte(0x11);
+    let receiver = "neutron1m6w8n0hluq7avn40hj0n6jnj8ejhykfrwfnnjh";
+
+    let storage = EthereumStorageLayoutBuilder::new(0)
+        .add_value(U256::from(1_000).to_be_bytes_trimmed_vec())
+        .add_value(owner)
+        .add_value([0x01])
+        .add_value(B256::repeat_byte(0x22))
+        .add_packed_values([(3, &(-5i32).to_be_bytes()[1..])])
+        .add_string_value(receiver)
+        .add_string_value("short")
+        .add_empty_slot()
+        .build();
+
+    assert_eq!(storage[0].slot().unwrap(), U256::ZERO);
+    assert_eq!(storage[0].to_u256().unwrap(), U256::from(1_000));
+    assert_eq!(storage[0].to_uint::<64, 1>().unwrap(), U64::from(1_000));
+    assert_eq!(storage[1].to_address().unwrap(), owner);
+    assert!(storage[2].to_bool().unwrap());
+    assert_eq!(storage[3].to_bytes32().unwrap(), B256::repeat_byte(0x22));
+    assert_eq!(
+        storage[4].to_int::<24, 1>().unwrap(),
+        I24::try_from(-5).unwrap()
+    );
+    assert!(!storage.last().unwrap().to_bool().unwrap());
+
+    let account = EthereumProvenAccount {
+        storage,
+        ..Default::default()
+    };
+
+    assert_eq!(account.read_string(U256::from(5)).unwrap(), receiver);
+    assert_eq!(account.read_string(U256::from(6)).unwrap(), "short");
+    assert_eq!(account.read_bytes(U256::from(6)).unwrap(), b"short");
+    assert_eq!(
+        account
+            .storage_value(U256::from(1))
+            .unwrap()
+            .to_address()
+            .unwrap(),
+        owner
+    );
+}
+
+#[test]
+fn mismatched_proven_values_are_rejected() {
+    let storage = EthereumStorageLayoutBuilder::new(0)
+        .add_value(B256::repeat_byte(0x22))
+        .add_value([0x02])
+        .add_value(U256::from(1u64 << 40).to_be_bytes_trimmed_vec())
+        .add_value([0x41])
+        .build();
+
+    assert!(storage[0].to_address().is_err());
+    assert!(storage[1].to_bool().is_err());
+    assert!(storage[2].to_uint::<32, 1>().is_err());
+    assert!(storage[2].to_int::<32, 1>().is_err());
+
+    let account = EthereumProvenAccount {
+        storage,
+        ..Default::default()
+    };
+
+    // a 32 bytes string, without its data slots
+    assert!(account.read_string(U256::from(3)).is_err());
+    assert!(account.storage_value(U256::from(4)).is_err());
+}
+
+#[test]
+fn oversized_bytes_lengths_are_rejected() {
+    // long `bytes` values store `len * 2 + 1`; the contract controls the length word
+    for len in [U256::from(33), U256::from(u64::MAX), U256::MAX >> 1usize] {
+        let word = ((len << 1usize) | U256::ONE).to_be_bytes::<32>();
+        let storage = EthereumStorageLayoutBuilder::new(0)
+            .add_value(U256::from_be_bytes(word).to_be_bytes_trimmed_vec())
+            .build();
+
+        let err = Ethereum::read_bytes(&storage, U256::ZERO).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<EthereumError>(),
+            Some(EthereumError::Decode { .. })
+        ));
+    }
+}
+
+#[test]
+fn proxy_slots_are_standard() {
+    for (slot, name) in [
+        (
+            EthereumProxySlot::Implementation,
+            "eip1967.proxy.implementation",
+        ),
+        (EthereumProxySlot::Admin, "eip1967.proxy.admin"),
+        (EthereumProxySlot::Beacon, "eip1967.proxy.beacon"),
+    ] {
+        assert_eq!(
+            slot.slot(),
+            U256::from_be_bytes(keccak256(name).0) - U256::ONE
+        );
+    }
+
+    assert_eq!(EthereumProxySlot::Proxiable.key(), keccak256("PROXIABLE"));
+}
+
+#[test]
+fn proxy_addresses_are_read() {
+    let implementation = Address::repeat_byte(0x11);
+    let admin = Address::repeat_byte(0x22);
+
+    let storage = EthereumStorageLayoutBuilder::new(0)
+        .add_value([0x01])
+        .add_proxy_slot(EthereumProxySlot::Implementation, implementation)
+        .add_proxy_slot(EthereumProxySlot::Admin, admin)
+        .add_value([0x02])
+        .build();
+
+    // the proxy slots don't move the current slot
+    assert_eq!(storage[3].slot().unwrap(), U256::ONE);
+
+    let account = EthereumProvenAccount {
+        storage,
+        ..Default::default()
+    };
+
+    assert_eq!(account.implementation().unwrap(), implementation);
+    assert_eq!(account.admin().unwrap(), admin);
+    assert!(account.proxy_address(EthereumProxySlot::Beacon).is_err());
+
+    // UUPS proxies store the implementation at the EIP-1822 slot
+    let storage = EthereumStorageLayoutBuilder::new(0)
+        .add_proxy_slot(EthereumProxySlot::Implementation, Address::ZERO)
+        .add_proxy_slot(EthereumProxySlot::Proxiable, implementation)
+        .build();
+
+    let account = EthereumProvenAccount {
+        storage,
+        ..Default::default()
+    };
+
+    assert_eq!(account.implementation().unwrap(), implementation);
+    assert!(account
+        .storage_value(EthereumProxySlot::Implementation.slot())
+        .unwrap()
+        .value
+        .is_none());
+}
+
+#[test]
+fn proxy_slots_are_requested() {
+    let args = EthereumStateProofArgsBuilder::new(Address::ZERO.to_string())
+        .with_block(1, [0; 32])
+        .with_proxy_slots([EthereumProxySlot::Implementation, EthereumProxySlot::Admin])
+        .with_storage(
+            EthereumStorageLayoutBuilder::new(0)
+                .add_value([0x01])
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let args: EthereumStateProofArgs = serde_json::from_value(args).unwrap();
+
+    assert_eq!(args.storage.len(), 1);
+    assert_eq!(
+        args.fetched,
+        [
+            EthereumProxySlot::Implementation.key().to_vec(),
+            EthereumProxySlot::Admin.key().to_vec(),
+        ]
+    );
+
+    let accounts = args.to_accounts();
+    let keys: Vec<_> = accounts[0]
+        .storage
+        .iter()
+        .map(|s| s.key.as_slice())
+        .collect();
+
+    assert_eq!(
+        keys,
+        [
+            &U256::ZERO.to_be_bytes::<32>()[..],
+            EthereumProxySlot::Implementation.key().as_slice(),
+            EthereumProxySlot::Admin.key().as_slice(),
+        ]
+    );
+}
+
+#[test]
+fn proxy_slots_are_fetched_with_provided_values() {
+    let implementation = Address::repeat_byte(0x11);
+    let proxy = [0x42; 20];
+
+    let (state_root, response) = storage_response(
+        proxy,
+        &[
+            (U256::ZERO.into(), U256::ONE),
+            (
+                EthereumProxySlot::Implementation.key(),
+                U256::from_be_slice(implementation.as_slice()),
+            ),
+            (EthereumProxySlot::Admin.key(), U256::ZERO),
+        ],
+    );
+
+    // the layout values are provided, but not the ones of the proxy slots
+    let args = EthereumStateProofArgsBuilder::new(Address::from(proxy).to_string())
+        .with_block(1, state_root.0)
+        .with_storage(
+            EthereumStorageLayoutBuilder::new(0)
+                .add_value([0x01])
+                .build(),
+        )
+        .with_proxy_slots([EthereumProxySlot::Implementation, EthereumProxySlot::Admin])
+        .build()
+        .unwrap();
+
+    let args: EthereumStateProofArgs = serde_json::from_value(args).unwrap();
+    let proof = Ethereum::encode_proof(response, args).unwrap();
+    let proven = Ethereum::verify(&proof).unwrap();
+
+    assert_eq!(
+        proven.storage_value(U256::ZERO).unwrap().to_u256().unwrap(),
+        U256::ONE
+    );
+    assert_eq!(proven.implementation().unwrap(), implementation);
+    assert_eq!(proven.admin().unwrap(), Address::ZERO);
+}
+
+#[test]
+fn payload_commitment_is_proven() {
+    let contract = [0x42; 20];
+    let slot = U256::from(5);
+
+    let (state_root, response) = storage_response(
+        contract,
+        &[(slot.into(), U256::from_be_bytes(keccak256(b"foo").0))],
+    );
+
+    let args = EthereumStateProofArgsBuilder::new(Address::from(contract).to_string())
+        .with_block(1, state_root.0)
+        .with_payload(b"foo".to_vec())
+        .with_payload_commitment(slot)
+        .build()
+        .unwrap();
+
+    let args: EthereumStateProofArgs = serde_json::from_value(args).unwrap();
+    let mut proof = Ethereum::encode_proof(response, args).unwrap();
+    let proven = Ethereum::verify_with_payload(&proof, slot).unwrap();
+
+    assert_eq!(proven.payload, b"foo");
+
+    // the relayer replaced the payload
+    proof.payload = b"bar".to_vec();
+
+    let err = Ethereum::verify_with_payload(&proof, slot).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<EthereumError>(),
+        Some(&EthereumError::PayloadMismatch {
+            key: slot.to_be_bytes::<32>().to_vec()
+        })
+    );
+
+    // the commitment must be proven at the slot of the circuit
+    assert!(Ethereum::verify_with_payload(&proof, U256::from(6)).is_err());
+}
+
+#[test]
+fn namespaced_layouts_start_at_their_base_slot() {
+    // `ERC20Storage` of OpenZeppelin v5
+    let base = U256::from_be_bytes(
+        b256!("52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00").0,
+    );
+
+    assert_eq!(Ethereum::erc7201_slot("openzeppelin.storage.ERC20"), base);
+
+    let holder = Address::repeat_byte(0x11);
+    let storage = EthereumStorageLayoutBuilder::new_namespace("openzeppelin.storage.ERC20")
+        .enter_mapping(holder)
+        .add_value(U256::from(100).to_be_bytes_trimmed_vec())
+        .build();
+
+    assert_eq!(
+        storage[0].slot().unwrap(),
+        EthereumMappingKey::from(holder).slot(base)
+    );
+
+    // `_totalSupply` follows the `_balances` and `_allowances` mappings
+    let storage = EthereumStorageLayoutBuilder::new_with_slot(base + U256::from(2))
+        .add_value(U256::from(100).to_be_bytes_trimmed_vec())
+        .build();
+
+    assert_eq!(storage[0].slot().unwrap(), base + U256::from(2));
+}
+
+/// Creates a state with the contract account holding the storage `slots`, returning its root
+/// with the `eth_getProof` response of the slots.
+fn storage_response(contract: [u8; 20], slots: &[(B256, U256)]) -> (B256, Value) {
+    let leaves = slots
+        .iter()
+        .filter(|(_, value)| !value.is_zero())
+        .map(|(key, value)| (Nibbles::unpack(keccak256(key)), alloy_rlp::encode(value)))
+        .collect();
+    let targets: Vec<_> = slots
+        .iter()
+        .map(|(key, _)| Nibbles::unpack(keccak256(key)))
+        .collect();
+    let (storage_root, storage_proofs) = common::trie(leaves, &targets);
+
+    let account = TrieAccount {
+        nonce: 1,
+        storage_root,
+        ..Default::default()
+    };
+
+    let contract_key = Nibbles::unpack(keccak256(contract));
+    let (state_root, mut account_proofs) = common::trie(
+        vec![
+            (contract_key.clone(), alloy_rlp::encode(account)),
+            (
+                Nibbles::unpack(keccak256([0x11; 20])),
+                alloy_rlp::encode(TrieAccount::default()),
+            ),
+        ],
+        &[contract_key],
+    );
+
+    let storage: Vec<_> = slots
+        .iter()
+        .zip(storage_proofs)
+        .map(|((key, value), proof)| {
+            json!({ "key": key, "value": value, "proof": common::bytes(proof) })
+        })
+        .collect();
+
+    let response = common::account_response(
+        contract,
+        &account,
+        account_proofs.pop().unwrap(),
+        json!(storage),
+    );
+
+    (state_root, response)
+}
@@ -0,0 +1,222 @@
+use alloy_consensus::{Eip658Value, Header, Receipt, ReceiptEnvelope, ReceiptWithBloom};
+use alloy_eips::eip2718::Encodable2718 as _;
+use alloy_primitives::{Address, Bloom, Bytes, Log, LogData, B256};
+use alloy_trie::Nibbles;
+use serde_json::{json, Value};
+use valence_coprocessor::StateProof;
+use valence_coprocessor_ethereum::{
+    Ethereum, EthereumBlockRange, EthereumError, EthereumNetwork, EthereumProofArgs,
+    EthereumReceiptProof, EthereumReceiptProofArgs,
+};
+
+mod common;
+
+const TARGET: u64 = 22_500_000;
+
+#[test]
+fn receipt_proof_works() {
+    let (proof, receipts) = receipt_proof(TARGET + 100, &[0, 2]);
+    let proven = Ethereum::verify_receipts(&proof).unwrap();
+
+    assert_eq!(proven.len(), 2);
+
+    assert_eq!(proven[0].block_number, TARGET);
+    assert_eq!(proven[0].transaction_index, 0);
+    assert_eq!(proven[0].tx_type, 2);
+    assert!(proven[0].status);
+    assert_eq!(proven[0].payload, b"receipts");
+
+    let log = &receipts[0].logs()[0];
+
+    assert_eq!(proven[0].logs.len(), 1);
+    assert_eq!(proven[0].logs[0].address, log.address.to_vec());
+    assert_eq!(proven[0].logs[0].topics, vec![log.topics()[0].0]);
+    assert_eq!(proven[0].logs[0].data, log.data.data.to_vec());
+
+    assert_eq!(proven[1].transaction_index, 2);
+    assert_eq!(proven[1].tx_type, 0);
+    assert!(!proven[1].status);
+    assert!(proven[1].logs.is_empty());
+    assert_eq!(proven[1].block_hash, proven[0].block_hash);
+}
+
+#[test]
+fn receipt_proof_rejects_tampered_receipts() {
+    let (mut proof, _) = receipt_proof(TARGET + 100, &[0]);
+    let mut receipts = EthereumReceiptProof::try_from_slice(&proof.proof).unwrap();
+
+    let last = receipts.receipts[0].receipt.len() - 1;
+    receipts.receipts[0].receipt[last] ^= 1;
+    proof.proof = receipts.to_vec();
+
+    assert!(Ethereum::verify_receipts(&proof).is_err());
+}
+
+#[test]
+fn receipt_proof_rejects_unanchored_headers() {
+    let (mut proof, _) = receipt_proof(TARGET + 100, &[0]);
+    let mut receipts = EthereumReceiptProof::try_from_slice(&proof.proof).unwrap();
+
+    let mut header: Header = alloy_rlp::decode_exact(&receipts.block.header).unwrap();
+    header.gas_used += 1;
+    receipts.block.header = alloy_rlp::encode(&header);
+    proof.proof = receipts.to_vec();
+
+    assert!(Ethereum::verify_receipts(&proof).is_err());
+}
+
+#[test]
+fn receipt_proof_rejects_blocks_outside_of_history_window() {
+    let window = Ethereum::HISTORY_SERVE_WINDOW;
+
+    assert!(Ethereum::verify_receipts(&receipt_proof(TARGET + window, &[0]).0).is_ok());
+    assert!(Ethereum::verify_receipts(&receipt_proof(TARGET + window + 1, &[0]).0).is_err());
+    assert!(Ethereum::verify_receipts(&receipt_proof(TARGET, &[0]).0).is_err());
+}
+
+#[test]
+fn receipt_proof_range_checks_the_proven_block() {
+    let (proof, _) = receipt_proof(TARGET + 100, &[0]);
+
+    for range in [
+        EthereumBlockRange::exact(TARGET),
+        EthereumBlockRange::default().with_max(TARGET),
+    ] {
+        assert!(Ethereum::verify_receipts_in_range(&proof, &range).is_ok());
+    }
+
+    // the validated block is in range, but not the block of the receipts
+    let range = EthereumBlockRange::exact(TARGET + 100);
+    let err = Ethereum::verify_receipts_in_range(&proof, &range).unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<EthereumError>(),
+        Some(&EthereumError::BlockOutOfRange {
+            number: TARGET,
+            range
+        })
+    );
+}
+
+#[test]
+fn receipt_proof_args_are_tagged() {
+    let args = EthereumReceiptProofArgs {
+        block: TARGET + 1,
+        root: [1; 32],
+        target: TARGET,
+        transactions: vec![3],
+        payload: vec![],
+        network: EthereumNetwork::Mainnet,
+    };
+
+    let value = serde_json::to_value(EthereumProofArgs::Receipt(args.clone())).unwrap();
+
+    assert_eq!(value["kind"], "receipt");
+    assert_eq!(
+        serde_json::from_value::<EthereumProofArgs>(value).unwrap(),
+        EthereumProofArgs::Receipt(args)
+    );
+}
+
+#[test]
+fn ordered_trie_proofs_keep_32_bytes_nodes() {
+    // leaves of a 1 byte path and a 29 bytes value are RLP encoded in exactly 32 bytes, so they
+    // are hashed into their parent instead of being embedded
+    let values: Vec<_> = (0..3u8).map(|i| vec![i; 29]).collect();
+    let (root, proofs) = common::ordered_trie(&values, &[0, 1, 2]);
+
+    for (i, proof) in proofs.into_iter().enumerate() {
+        assert!(proof.iter().any(|node| node.len() == B256::len_bytes()));
+
+        let key = Nibbles::unpack(alloy_rlp::encode(i));
+        let proof: Vec<_> = proof.into_iter().map(Bytes::from).collect();
+
+        alloy_trie::proof::verify_proof(root, key, Some(values[i].clone()), &proof).unwrap();
+    }
+}
+
+/// Encodes a receipt proof of the `transactions` of the target block, validated at `block`.
+fn receipt_proof(block: u64, transactions: &[u64]) -> (StateProof, Vec<ReceiptEnvelope>) {
+    let transfer = Log {
+        address: Address::repeat_byte(0xaa),
+        data: LogData::new_unchecked(vec![B256::repeat_byte(0x01)], Bytes::from(vec![0x42; 32])),
+    };
+
+    let receipts = vec![
+        ReceiptEnvelope::Eip1559(receipt(true, 21_000, vec![transfer])),
+        ReceiptEnvelope::Eip2930(receipt(true, 42_000, vec![])),
+        ReceiptEnvelope::Legacy(receipt(false, 63_000, vec![])),
+    ];
+
+    let encoded: Vec<_> = receipts.iter().map(|r| r.encoded_2718()).collect();
+    let (receipts_root, _) = common::ordered_trie(&encoded, &[]);
+
+    let header = Header {
+        number: TARGET,
+        receipts_root,
+        gas_used: 63_000,
+        ..Default::default()
+    };
+
+    let (root, header, history) = common::history(&header);
+    let responses = receipts.iter().map(receipt_response).collect();
+
+    let args = EthereumReceiptProofArgs {
+        block,
+        root: root.0,
+        target: TARGET,
+        transactions: transactions.to_vec(),
+        payload: b"receipts".to_vec(),
+        network: EthereumNetwork::Mainnet,
+    };
+
+    let proof =
+        Ethereum::encode_receipt_proof(header, Value::Array(responses), history, args).unwrap();
+
+    (proof, receipts)
+}
+
+fn receipt(status: bool, cumulative_gas_used: u64, logs: Vec<Log>) -> ReceiptWithBloom {
+    ReceiptWithBloom {
+        receipt: Receipt {
+            status: Eip658Value::Eip658(status),
+            cumulative_gas_used: cumulative_gas_used.into(),
+            logs,
+        },
+        logs_bloom: Bloom::ZERO,
+    }
+}
+
+/// Mocks the `eth_getBlockReceipts` entry of a receipt.
+fn receipt_response(receipt: &ReceiptEnvelope) -> Value {
+    let mut value = serde_json::to_value(receipt).unwrap();
+
+    for log in value["logs"].as_array_mut().unwrap() {
+        for field in [
+            "blockHash",
+            "blockNumber",
+            "blockTimestamp",
+            "transactionHash",
+            "transactionIndex",
+            "logIndex",
+        ] {
+            log[field] = Value::Null;
+        }
+    }
+
+    let metadata = json!({
+        "transactionHash": B256::ZERO,
+        "gasUsed": "0x5208",
+        "effectiveGasPrice": "0x1",
+        "from": Address::ZERO,
+        "to": Address::ZERO,
+        "contractAddress": null,
+    });
+
+    value
+        .as_object_mut()
+        .unwrap()
+        .extend(metadata.as_object().unwrap().clone());
+
+    value
+}
@@ -0,0 +1,213 @@
+use alloy_primitives::{keccak256, Address, I256, U256};
+use alloy_sol_types::SolValue as _;
+use valence_coprocessor_ethereum::{
+    controller::EthereumStorageLayoutBuilder, EthereumError, EthereumSlotPosition,
+    EthereumSolcStorageLayout, EthereumSolcValue,
+};
+
+const RECEIVER: &str = "neutron1m6w8n0hluq7avn40hj0n6jnj8ejhykfrwfnnjh";
+
+fn layout() -> EthereumSolcStorageLayout {
+    EthereumSolcStorageLayout::from_json(include_str!("../../../assets/storage-layout.json"))
+        .unwrap()
+}
+
+#[test]
+fn struct_paths_match_the_layout_builder() {
+    let layout = layout();
+    let owner = Address::repeat_byte(0x11);
+
+    let storage = layout
+        .storage()
+        .with_value("withdrawals[42].id", 42u64.to_be_bytes())
+        .with_value("withdrawals[42].owner", owner)
+        .with_value(
+            "withdrawals[42].redemptionRate",
+            U256::from(3).to_be_bytes::<32>(),
+        )
+        .with_value("withdrawals[42].sharesAmount", 1_000u64.to_be_bytes())
+        .with_value("withdrawals[42].receiver", RECEIVER)
+        .build()
+        .unwrap();
+
+    let expected = EthereumStorageLayoutBuilder::new_mapping(42, 9)
+        .add_packed_values([(8, &42u64.to_be_bytes()[..]), (20, owner.as_slice())])
+        .add_value(U256::from(3).to_be_bytes_trimmed_vec())
+        .add_value(U256::from(1_000).to_be_bytes_trimmed_vec())
+        .add_string_value(RECEIVER)
+        .build();
+
+    assert_eq!(storage, expected);
+
+    let decode = |path| layout.decode(path, &storage).unwrap();
+
+    assert_eq!(
+        decode("withdrawals[42].id"),
+        EthereumSolcValue::Uint(U256::from(42))
+    );
+    assert_eq!(
+        decode("withdrawals[42].owner"),
+        EthereumSolcValue::Address(owner)
+    );
+    assert_eq!(
+        decode("withdrawals[0x2a].sharesAmount"),
+        EthereumSolcValue::Uint(U256::from(1_000))
+    );
+    assert_eq!(
+        decode("withdrawals[42].receiver"),
+        EthereumSolcValue::String(RECEIVER.to_string())
+    );
+}
+
+#[test]
+fn state_variables_are_resolved() {
+    let layout = layout();
+    let owner = Address::repeat_byte(0x11);
+    let spender = Address::repeat_byte(0x22);
+
+    let position = |path| layout.resolve(path).unwrap().position;
+    let slot = |preimage: Vec<u8>| U256::from_be_bytes(keccak256(preimage).0);
+
+    assert_eq!(
+        position("delta"),
+        EthereumSlotPosition::new(U256::ZERO, 21, 4).unwrap()
+    );
+
+    assert_eq!(
+        position("balances[0x1111111111111111111111111111111111111111]").slot(),
+        slot((owner, U256::from(2)).abi_encode())
+    );
+
+    let allowances = slot((owner, U256::from(7)).abi_encode());
+
+    assert_eq!(
+        position(
+            "allowances[0x1111111111111111111111111111111111111111]\
+             [0x2222222222222222222222222222222222222222]"
+        )
+        .slot(),
+        slot((spender, allowances).abi_encode())
+    );
+
+    assert_eq!(
+        position("names[\"alice\"]").slot(),
+        slot(("alice".to_string(), U256::from(5)).abi_encode_packed())
+    );
+
+    assert_eq!(
+        position("ids[5]"),
+        EthereumSlotPosition::new(slot(U256::from(3).abi_encode()) + U256::from(1), 8, 8).unwrap()
+    );
+
+    assert_eq!(
+        position("selectors[2]"),
+        EthereumSlotPosition::new(U256::from(4), 8, 4).unwrap()
+    );
+
+    assert_eq!(
+        position("requests[2].receiver").slot(),
+        slot(U256::from(10).abi_encode()) + U256::from(11)
+    );
+}
+
+#[test]
+fn state_variables_are_decoded() {
+    let layout = layout();
+    let owner = Address::repeat_byte(0x11);
+
+    let storage = layout
+        .storage()
+        .with_value("owner", owner)
+        .with_value("paused", [1])
+        .with_value("delta", (-7i32).to_be_bytes())
+        .with_value("status", [2])
+        .with_value("selectors[1]", [0xa9, 0x05, 0x9c, 0xbb])
+        .with_value("ids", 6u64.to_be_bytes())
+        .with_value("ids[5]", 9u64.to_be_bytes())
+        .with_value("names[\"alice\"]", [1])
+        .with_value("description", "vault")
+        .build()
+        .unwrap();
+
+    let decode = |path| layout.decode(path, &storage).unwrap();
+
+    assert_eq!(decode("owner"), EthereumSolcValue::Address(owner));
+    assert_eq!(decode("paused"), EthereumSolcValue::Bool(true));
+    assert_eq!(
+        decode("delta"),
+        EthereumSolcValue::Int(I256::try_from(-7).unwrap())
+    );
+    assert_eq!(decode("status"), EthereumSolcValue::Uint(U256::from(2)));
+    assert_eq!(
+        decode("selectors[1]"),
+        EthereumSolcValue::FixedBytes(vec![0xa9, 0x05, 0x9c, 0xbb])
+    );
+    assert_eq!(
+        decode("selectors[0]"),
+        EthereumSolcValue::FixedBytes(vec![0; 4])
+    );
+    assert_eq!(decode("ids"), EthereumSolcValue::Uint(U256::from(6)));
+    assert_eq!(decode("ids[5]"), EthereumSolcValue::Uint(U256::from(9)));
+    assert_eq!(decode("names[\"alice\"]"), EthereumSolcValue::Bool(true));
+    assert_eq!(
+        decode("description"),
+        EthereumSolcValue::String("vault".to_string())
+    );
+
+    assert!(layout.decode("totalShares", &storage).is_err());
+}
+
+#[test]
+fn invalid_paths_are_rejected() {
+    let layout = layout();
+
+    for path in [
+        "",
+        "unknown",
+        "owner.id",
+        "owner[1]",
+        "withdrawals.id",
+        "withdrawals[foo]",
+        "withdrawals[42].unknown",
+        "selectors[3]",
+        "ids[-1]",
+        "names[alice",
+        ".owner",
+        "requests[1]..id",
+    ] {
+        assert!(layout.resolve(path).is_err(), "{path}");
+    }
+
+    for path in ["withdrawals", "withdrawals[42]", "requests[1]", "selectors"] {
+        assert!(
+            layout.storage().with_value(path, [1]).build().is_err(),
+            "{path}"
+        );
+    }
+}
+
+#[test]
+fn values_overflowing_their_slot_are_rejected() {
+    assert!(EthereumSlotPosition::new(U256::ZERO, 28, 4).is_ok());
+    assert!(EthereumSlotPosition::new(U256::ZERO, 29, 4).is_err());
+    assert!(EthereumSlotPosition::new(U256::ZERO, usize::MAX, 4).is_err());
+
+    let layout = EthereumSolcStorageLayout::from_json(
+        r#"{
+            "storage": [
+                { "label": "delta", "offset": 30, "slot": "0", "type": "t_int32" }
+            ],
+            "types": {
+                "t_int32": { "encoding": "inplace", "label": "int32", "numberOfBytes": "4" }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let err = layout.resolve("delta").unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<EthereumError>(),
+        Some(EthereumError::Config { .. })
+    ));
+}
@@ -0,0 +1,126 @@
+use alloy_primitives::{aliases::I24, Address, Bytes, FixedBytes, U256};
+use valence_coprocessor_ethereum::{
+    controller::EthereumStorageLayoutBuilder, EthereumSolcStorageLayout, EthereumStorageLayout,
+    StorageLayout,
+};
+
+alloy_sol_types::sol! {
+    #![sol(extra_derives(Debug, PartialEq, StorageLayout))]
+    struct WithdrawRequest {
+        uint64 id;
+        address owner;
+        uint256 redemptionRate;
+        uint256 sharesAmount;
+        string receiver;
+    }
+
+    struct Position {
+        bool open;
+        int24 tick;
+        bytes4 selector;
+        WithdrawRequest request;
+        uint8 flags;
+        uint64[] amounts;
+        address[3] signers;
+        bytes data;
+    }
+}
+
+fn withdraw(id: u64) -> WithdrawRequest {
+    WithdrawRequest {
+        id,
+        owner: Address::repeat_byte(0x11),
+        redemptionRate: U256::from(3),
+        sharesAmount: U256::from(1_000),
+        receiver: "neutron1m6w8n0hluq7avn40hj0n6jnj8ejhykfrwfnnjh".to_string(),
+    }
+}
+
+#[test]
+fn derived_layouts_match_the_layout_builder() {
+    let withdraw = withdraw(42);
+
+    assert_eq!(WithdrawRequest::SIZE, 128);
+
+    let expected = EthereumStorageLayoutBuilder::new_mapping(42, 9)
+        .add_packed_values([
+            (8, &withdraw.id.to_be_bytes()[..]),
+            (20, withdraw.owner.as_slice()),
+        ])
+        .add_value(withdraw.redemptionRate.to_be_bytes_trimmed_vec())
+        .add_value(withdraw.sharesAmount.to_be_bytes_trimmed_vec())
+        .add_string_value(&withdraw.receiver)
+        .add_value([0x01])
+        .build();
+
+    let storage = EthereumStorageLayoutBuilder::new_mapping(42, 9)
+        .add_layout(&withdraw)
+        .add_value([0x01])
+        .build();
+
+    assert_eq!(storage, expected);
+}
+
+#[test]
+fn derived_layouts_match_the_compiler_layout() {
+    let layout =
+        EthereumSolcStorageLayout::from_json(include_str!("../../../assets/storage-layout.json"))
+            .unwrap();
+
+    let requests = vec![withdraw(1), withdraw(2)];
+    let storage = requests.to_storage(U256::from(10));
+
+    let mut expected = layout.storage().with_value("requests", [2]);
+
+    for (i, request) in requests.iter().enumerate() {
+        expected = expected
+            .with_value(&format!("requests[{i}].id"), request.id.to_be_bytes())
+            .with_value(&format!("requests[{i}].owner"), request.owner)
+            .with_value(
+                &format!("requests[{i}].redemptionRate"),
+                request.redemptionRate.to_be_bytes::<32>(),
+            )
+            .with_value(
+                &format!("requests[{i}].sharesAmount"),
+                request.sharesAmount.to_be_bytes::<32>(),
+            )
+            .with_value(&format!("requests[{i}].receiver"), &request.receiver);
+    }
+
+    assert_eq!(storage, expected.build().unwrap());
+    assert_eq!(
+        Vec::<WithdrawRequest>::from_storage(U256::from(10), &storage).unwrap(),
+        requests
+    );
+}
+
+#[test]
+fn derived_layouts_are_decoded() {
+    let position = Position {
+        open: true,
+        tick: I24::try_from(-887_272).unwrap(),
+        selector: FixedBytes([0xa9, 0x05, 0x9c, 0xbb]),
+        request: withdraw(7),
+        flags: 0x80,
+        amounts: vec![1, 2, 3, 4, 5],
+        signers: [
+            Address::repeat_byte(0x01),
+            Address::ZERO,
+            Address::repeat_byte(0x03),
+        ],
+        data: Bytes::from_static(b"data"),
+    };
+
+    // open, tick and selector are packed, request takes 4 slots, flags 1, amounts 1, signers 3
+    // and data 1
+    assert_eq!(Position::SIZE, 11 * 32);
+
+    let slot = U256::from(5);
+    let storage = position.to_storage(slot);
+
+    assert_eq!(Position::from_storage(slot, &storage).unwrap(), position);
+
+    let storage: Vec<_> = storage.into_iter().skip(1).collect();
+
+    assert!(Position::from_storage(slot, &storage).is_err());
+}
@@ -0,0 +1,203 @@
+use alloy_consensus::{
+    Header, SignableTransaction as _, TxEip1559, TxEip2930, TxEip4844, TxEip7702, TxEnvelope,
+    TxLegacy,
+};
+use alloy_eips::eip2718::Encodable2718 as _;
+use alloy_primitives::{keccak256, Address, Bytes, PrimitiveSignature, TxKind, B256, U256};
+use serde_json::{json, Value};
+use valence_coprocessor::StateProof;
+use valence_coprocessor_ethereum::{
+    Ethereum, EthereumBlockRange, EthereumError, EthereumNetwork, EthereumTransactionProof,
+    EthereumTransactionProofArgs,
+};
+
+mod common;
+
+const TARGET: u64 = 22_500_000;
+
+#[test]
+fn transaction_proof_works() {
+    let transactions = transactions();
+    let proof = transaction_proof(&transactions, vec![0, 1, 2, 3, 4], vec![]);
+    let proven = Ethereum::verify_transactions(&proof).unwrap();
+
+    assert_eq!(proven.len(), transactions.len());
+
+    for (i, (proven, tx)) in proven.iter().zip(transactions.iter()).enumerate() {
+        let encoded = tx.encoded_2718();
+
+        assert_eq!(proven.block_number, TARGET);
+        assert_eq!(proven.transaction_index, i as u64);
+        assert_eq!(proven.tx_type, tx.tx_type() as u8);
+        assert_eq!(proven.hash, keccak256(&encoded).0);
+        assert_eq!(proven.nonce, i as u64);
+        assert_eq!(proven.transaction, encoded);
+        assert_eq!(proven.signature_hash, tx.signature_hash().0);
+        assert_eq!(proven.signature, tx.signature().as_bytes().to_vec());
+    }
+
+    let deposit = &proven[2];
+
+    assert_eq!(deposit.chain_id, Some(1));
+    assert_eq!(deposit.to, Some(vec![0xaa; 20]));
+    assert_eq!(U256::from_be_bytes(deposit.value), U256::from(1_000_000));
+    assert_eq!(deposit.input, b"deposit".to_vec());
+    assert_eq!(deposit.payload, b"transactions");
+
+    assert_eq!(proven[0].chain_id, None);
+    assert_eq!(proven[0].to, None);
+}
+
+#[test]
+fn transaction_proof_by_hash_works() {
+    let transactions = transactions();
+    let hash = keccak256(transactions[3].encoded_2718()).0;
+
+    let proof = transaction_proof(&transactions, vec![1], vec![hash]);
+    let proven = Ethereum::verify_transactions(&proof).unwrap();
+
+    assert_eq!(proven.len(), 2);
+    assert_eq!(proven[0].transaction_index, 1);
+    assert_eq!(proven[1].transaction_index, 3);
+    assert_eq!(proven[1].hash, hash);
+}
+
+#[test]
+fn transaction_proof_rejects_tampered_transactions() {
+    let transactions = transactions();
+    let mut proof = transaction_proof(&transactions, vec![2], vec![]);
+    let mut inclusion = EthereumTransactionProof::try_from_slice(&proof.proof).unwrap();
+
+    inclusion.transactions[0].transaction = transactions[1].encoded_2718();
+    proof.proof = inclusion.to_vec();
+
+    assert!(Ethereum::verify_transactions(&proof).is_err());
+}
+
+#[test]
+fn transaction_proof_range_checks_the_proven_block() {
+    let transactions = transactions();
+    let proof = transaction_proof(&transactions, vec![0], vec![]);
+
+    assert!(proof.number > TARGET);
+
+    let range = EthereumBlockRange::exact(TARGET);
+    assert!(Ethereum::verify_transactions_in_range(&proof, &range).is_ok());
+
+    // the validated block is in range, but not the block of the transactions
+    let range = EthereumBlockRange::exact(proof.number);
+    let err = Ethereum::verify_transactions_in_range(&proof, &range).unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<EthereumError>(),
+        Some(&EthereumError::BlockOutOfRange {
+            number: TARGET,
+            range
+        })
+    );
+}
+
+/// A transaction of each supported type, with the nonce set to its index.
+fn transactions() -> Vec<TxEnvelope> {
+    let signature = PrimitiveSignature::new(U256::from(1), U256::from(2), false);
+    let to = Address::repeat_byte(0xaa);
+
+    vec![
+        TxLegacy {
+            nonce: 0,
+            gas_price: 1,
+            gas_limit: 100_000,
+            to: TxKind::Create,
+            input: Bytes::from_static(b"init code"),
+            ..Default::default()
+        }
+        .into_signed(signature)
+        .into(),
+        TxEip2930 {
+            chain_id: 1,
+            nonce: 1,
+            gas_price: 1,
+            gas_limit: 21_000,
+            to: TxKind::Call(to),
+            ..Default::default()
+        }
+        .into_signed(signature)
+        .into(),
+        TxEip1559 {
+            chain_id: 1,
+            nonce: 2,
+            gas_limit: 50_000,
+            max_fee_per_gas: 2,
+            max_priority_fee_per_gas: 1,
+            to: TxKind::Call(to),
+            value: U256::from(1_000_000),
+            input: Bytes::from_static(b"deposit"),
+            ..Default::default()
+        }
+        .into_signed(signature)
+        .into(),
+        TxEip4844 {
+            chain_id: 1,
+            nonce: 3,
+            gas_limit: 21_000,
+            to,
+            blob_versioned_hashes: vec![B256::repeat_byte(0x01)],
+            max_fee_per_blob_gas: 1,
+            ..Default::default()
+        }
+        .into_signed(signature)
+        .into(),
+        TxEip7702 {
+            chain_id: 1,
+            nonce: 4,
+            gas_limit: 21_000,
+            to,
+            ..Default::default()
+        }
+        .into_signed(signature)
+        .into(),
+    ]
+}
+
+/// Encodes a transaction proof of a block with the given transactions.
+fn transaction_proof(
+    transactions: &[TxEnvelope],
+    indexes: Vec<u64>,
+    hashes: Vec<[u8; 32]>,
+) -> StateProof {
+    let encoded: Vec<_> = transactions.iter().map(|tx| tx.encoded_2718()).collect();
+    let (transactions_root, _) = common::ordered_trie(&encoded, &[]);
+
+    let header = Header {
+        number: TARGET,
+        transactions_root,
+        ..Default::default()
+    };
+
+    let (root, mut block, history) = common::history(&header);
+
+    block["transactions"] = transactions.iter().map(transaction_response).collect();
+
+    let args = EthereumTransactionProofArgs {
+        block: TARGET + 1,
+        root: root.0,
+        target: TARGET,
+        transactions: indexes,
+        hashes,
+        payload: b"transactions".to_vec(),
+        network: EthereumNetwork::Mainnet,
+    };
+
+    Ethereum::encode_transaction_proof(block, history, args).unwrap()
+}
+
+/// Mocks the `eth_getBlockByNumber` entry of a transaction.
+fn transaction_response(tx: &TxEnvelope) -> Value {
+    let mut value = serde_json::to_value(tx).unwrap();
+    let object = value.as_object_mut().unwrap();
+
+    object.insert("from".into(), json!(Address::ZERO));
+    object.entry("gasPrice").or_insert(json!("0x1"));
+
+    value
+}
@@ -0,0 +1,90 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Index, Member};
+
+/// Implements `EthereumStorageLayout` for a struct, laying out its fields in declaration order as
+/// Solidity does.
+///
+/// Works for `sol!` structs, with `#![sol(extra_derives(StorageLayout))]`.
+#[proc_macro_derive(StorageLayout)]
+pub fn derive_storage_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input,
+                "StorageLayout can only be derived for structs",
+            ))
+        }
+    };
+
+    let members: Vec<_> = match fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|f| (Member::from(f.ident.clone().unwrap()), &f.ty))
+            .collect(),
+        Fields::Unnamed(fields) => fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (Member::from(Index::from(i)), &f.ty))
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    let krate = quote!(::valence_coprocessor_ethereum);
+    let layout = quote!(#krate::EthereumStorageLayout);
+
+    let sizes = members
+        .iter()
+        .map(|(_, ty)| quote!((<#ty as #layout>::SIZE, <#ty as #layout>::PACKED)));
+
+    let store = members.iter().map(
+        |(member, ty)| quote!(<#ty as #layout>::store(&self.#member, cursor.next_member::<#ty>(), words);),
+    );
+
+    let load = members.iter().map(
+        |(member, ty)| quote!(#member: <#ty as #layout>::load(cursor.next_member::<#ty>(), storage)?,),
+    );
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #layout for #name #ty_generics #where_clause {
+            const SIZE: usize = #krate::EthereumStorageCursor::size_of(&[#(#sizes),*]);
+            const PACKED: bool = false;
+
+            fn store(
+                &self,
+                position: #krate::EthereumSlotPosition,
+                words: &mut #krate::EthereumStorageWords,
+            ) {
+                let mut cursor = #krate::EthereumStorageCursor::new(position.slot());
+
+                #(#store)*
+            }
+
+            fn load(
+                position: #krate::EthereumSlotPosition,
+                storage: &[#krate::EthereumStorageProofArg],
+            ) -> #krate::__private::anyhow::Result<Self> {
+                let mut cursor = #krate::EthereumStorageCursor::new(position.slot());
+
+                Ok(Self {
+                    #(#load)*
+                })
+            }
+        }
+    })
+}
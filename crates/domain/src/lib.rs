@@ -18,6 +18,7 @@ pub fn validate_block_impl(args: Value) -> anyhow::Result<ValidatedBlock> {
         .ok_or_else(|| anyhow::anyhow!("No proof provided"))
         .and_then(ProvenState::decode)?;
 
+    // the wrapper proof commits the execution block hash with the state root
     let payload = proof.wrapper.pack_to_vec();
     let mut block = service.apply(proof)?;
 
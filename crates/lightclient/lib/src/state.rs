@@ -69,6 +69,7 @@ impl State {
         Ok(Output {
             block_number: *execution.block_number(),
             state_root: *execution.state_root(),
+            block_hash: *execution.block_hash(),
         })
     }
 
@@ -142,4 +143,5 @@ fn state_apply_works() {
 
     assert_eq!(&output.block_number, execution.block_number());
     assert_eq!(&output.state_root, execution.state_root());
+    assert_eq!(&output.block_hash, execution.block_hash());
 }
@@ -88,6 +88,8 @@ impl ProvenState {
         Base64::decode(base64).and_then(Self::try_from_slice)
     }
 
+    /// Returns the validated block committed by the wrapper proof, with the execution block hash
+    /// as payload.
     pub fn to_validated_block(&self) -> anyhow::Result<ValidatedBlock> {
         self.wrapper
             .decode()
@@ -106,6 +108,10 @@ pub struct Config {
 pub struct Output {
     pub block_number: u64,
     pub state_root: B256,
+
+    /// Hash of the execution block, committed by the wrapper circuit as the validated block
+    /// payload.
+    pub block_hash: B256,
 }
 
 impl Default for Output {
@@ -22,7 +22,9 @@ pub fn main() {
     let output = inputs.state.to_output().unwrap();
     let number = output.block_number;
     let root = *output.state_root;
-    let payload = Default::default();
+
+    // binds the header of the validated block to its state root
+    let payload = output.block_hash.to_vec();
 
     let output = ValidatedBlock {
         number,